@@ -0,0 +1,454 @@
+use std::cmp::{max, min};
+use std::fmt;
+use std::str::FromStr;
+
+
+/// Values with a well-defined next and previous value, like integers.
+///
+/// Closed intervals of these can be joined when they touch, split around another
+/// interval and measured.
+pub trait Discrete: Ord + Copy {
+    fn successor(&self) -> Option<Self>;
+    fn predecessor(&self) -> Option<Self>;
+    /// Number of values from `self` to `other`, both included; `other` must not be smaller.
+    fn count_to(&self, other: &Self) -> u64;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {$(
+        impl Discrete for $t {
+            fn successor(&self) -> Option<$t> {
+                self.checked_add(1)
+            }
+
+            fn predecessor(&self) -> Option<$t> {
+                self.checked_sub(1)
+            }
+
+            fn count_to(&self, other: &$t) -> u64 {
+                // Saturates for the full range of a 64-bit type, which has one value too many
+                (*other as i128 - *self as i128 + 1).min(u64::MAX as i128) as u64
+            }
+        }
+    )*};
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+
+/// A closed interval, i.e. both `start` and `end` belong to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+impl<T: Ord + Copy> Interval<T> {
+    /// Returns `None` if `start` is greater than `end`.
+    pub fn new(start: T, end: T) -> Option<Interval<T>> {
+        (start <= end).then_some(Interval { start, end })
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn contains_value(&self, value: &T) -> bool {
+        self.start <= *value && *value <= self.end
+    }
+
+    /// Whether every value of `other` is also in `self`.
+    pub fn contains(&self, other: &Interval<T>) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        Interval::new(max(self.start, other.start), min(self.end, other.end))
+    }
+}
+
+impl<T: Discrete> Interval<T> {
+    /// Whether the intervals overlap or one starts right after the other ends.
+    pub fn is_contiguous(&self, other: &Interval<T>) -> bool {
+        self.overlaps(other)
+            || self.end.successor() == Some(other.start)
+            || other.end.successor() == Some(self.start)
+    }
+
+    /// Returns `None` if the union has a gap, since it wouldn't be a single interval.
+    pub fn union(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let (start, end): (T, T) = (min(self.start, other.start), max(self.end, other.end));
+        self.is_contiguous(other).then_some(Interval { start, end })
+    }
+
+    /// The values of `self` that aren't in `other`, in increasing order: none, one interval,
+    /// or two when `other` is strictly inside `self`.
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+
+        let before: Option<Interval<T>> = other.start.predecessor()
+            .and_then(|end| Interval::new(self.start, min(end, self.end)));
+        let after: Option<Interval<T>> = other.end.successor()
+            .and_then(|start| Interval::new(max(start, self.start), self.end));
+
+        before.into_iter().chain(after).collect()
+    }
+
+    /// Number of values in the interval, which is never zero.
+    pub fn length(&self) -> u64 {
+        self.start.count_to(&self.end)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Parses `<start>-<end>`, e.g. `2-4` or `-5--3`.
+impl<T> FromStr for Interval<T>
+where
+    T: FromStr + Ord + Copy,
+    T::Err: fmt::Display,
+{
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Interval<T>, String> {
+        // The first character can't be the separator, it would be the sign of the start
+        let separator: usize = string.char_indices().skip(1)
+            .find(|(_, c)| *c == '-')
+            .map(|(i, _)| i)
+            .ok_or_else(|| String::from("expected <start>-<end>"))?;
+
+        let (start, end): (&str, &str) = (&string[..separator], &string[separator + 1..]);
+        let start: T = start.trim().parse().map_err(|error| format!("invalid start: {error}"))?;
+        let end: T = end.trim().parse().map_err(|error| format!("invalid end: {error}"))?;
+
+        Interval::new(start, end).ok_or_else(|| String::from("the interval ends before it starts"))
+    }
+}
+
+
+/// A set of values stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { intervals: Vec::new() }
+    }
+
+    /// Values covered by at least `k` of the intervals, found with a sweep over their
+    /// bounds; `k` should be positive, since no universe is known to count the gaps in.
+    pub fn covered_at_least(intervals: &[Interval<T>], k: usize) -> IntervalSet<T> {
+        // At the same value, starts go first, as both bounds belong to the intervals
+        let mut bounds: Vec<(T, bool)> = intervals.iter()
+            .flat_map(|x| [(x.start, false), (x.end, true)])
+            .collect();
+        bounds.sort_unstable();
+
+        let mut covered: Vec<Interval<T>> = Vec::new();
+        let mut count: usize = 0;
+        let mut start: Option<T> = None;
+
+        for (value, is_end) in bounds {
+            match is_end {
+                false => {
+                    count += 1;
+                    if count == k {
+                        start = Some(value);
+                    }
+                },
+                true => {
+                    if count == k {
+                        covered.extend(start.take().map(|start| Interval { start, end: value }));
+                    }
+                    count -= 1;
+                },
+            }
+        }
+
+        covered.into_iter().collect()
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let first: usize = self.intervals
+            .partition_point(|x| x.end < interval.start && !x.is_contiguous(&interval));
+        let last: usize = first + self.intervals[first..].iter()
+            .take_while(|x| x.is_contiguous(&interval))
+            .count();
+
+        let merged: Interval<T> = self.intervals[first..last].iter()
+            .fold(interval, |merged, x| merged.union(x).expect("The intervals are contiguous"));
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn contains_value(&self, value: &T) -> bool {
+        let i: usize = self.intervals.partition_point(|x| x.end < *value);
+        self.intervals.get(i).is_some_and(|x| x.contains_value(value))
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.intervals.iter().chain(other.intervals.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals: Vec<Interval<T>> = Vec::new();
+        let (mut i, mut j): (usize, usize) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            intervals.extend(a.intersection(b));
+            match a.end < b.end {
+                true => i += 1,
+                false => j += 1,
+            }
+        }
+
+        // Gaps of either set are still gaps, so the pieces can't be adjacent
+        IntervalSet { intervals }
+    }
+
+    /// Values of `universe` that aren't in the set.
+    pub fn complement(&self, universe: &Interval<T>) -> IntervalSet<T> {
+        let mut intervals: Vec<Interval<T>> = Vec::new();
+        let mut next: Option<T> = Some(universe.start);
+
+        for interval in self.intervals.iter() {
+            let Some(start) = next else {
+                break;
+            };
+            if interval.start > universe.end {
+                break;
+            }
+            if interval.end < start {
+                continue;
+            }
+
+            if start < interval.start {
+                let end: T = interval.start.predecessor().expect("Something is smaller");
+                intervals.push(Interval { start, end });
+            }
+            next = interval.end.successor();
+        }
+
+        intervals.extend(next.and_then(|start| Interval::new(start, universe.end)));
+        IntervalSet { intervals }
+    }
+
+    /// Total number of values, saturating at `u64::MAX`.
+    pub fn length(&self) -> u64 {
+        self.intervals.iter().fold(0, |total: u64, x| total.saturating_add(x.length()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+}
+
+impl<T: Discrete> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(intervals: I) -> IntervalSet<T> {
+        let mut sorted: Vec<Interval<T>> = intervals.into_iter().collect();
+        sorted.sort_unstable();
+
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+        for interval in sorted {
+            match merged.last_mut().and_then(|last| last.union(&interval).map(|x| (last, x))) {
+                Some((last, union)) => *last = union,
+                None => merged.push(interval),
+            }
+        }
+
+        IntervalSet { intervals: merged }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(intervals: &[Interval<i8>]) -> Vec<i8> {
+        intervals.iter().flat_map(|x| x.start..=x.end).collect()
+    }
+
+    // Every interval with both bounds in -4..=4
+    fn small_intervals() -> Vec<Interval<i8>> {
+        (-4..=4).flat_map(|start| (start..=4).map(move |end| Interval { start, end })).collect()
+    }
+
+    #[test]
+    fn difference_matches_the_values() {
+        for a in small_intervals() {
+            for b in small_intervals() {
+                let difference: Vec<Interval<i8>> = a.difference(&b);
+                let expected: Vec<i8> = values(&[a]).into_iter()
+                    .filter(|x| !b.contains_value(x))
+                    .collect();
+
+                assert_eq!(values(&difference), expected, "{a} minus {b}");
+                assert!(difference.windows(2).all(|x| !x[0].is_contiguous(&x[1])), "{a} minus {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn difference_at_the_bounds_of_the_type() {
+        let full: Interval<u8> = Interval::new(0, 255).unwrap();
+        let difference = |start: u8, end: u8| full.difference(&Interval::new(start, end).unwrap());
+
+        assert_eq!(difference(0, 0), vec![Interval::new(1, 255).unwrap()]);
+        assert_eq!(difference(255, 255), vec![Interval::new(0, 254).unwrap()]);
+        assert_eq!(difference(0, 255), vec![]);
+        assert_eq!(
+            difference(1, 254), vec![Interval::new(0, 0).unwrap(), Interval::new(255, 255).unwrap()]
+        );
+    }
+
+    #[test]
+    fn union_matches_the_values() {
+        for a in small_intervals() {
+            for b in small_intervals() {
+                let mut expected: Vec<i8> = values(&[a, b]);
+                expected.sort_unstable();
+                expected.dedup();
+                let is_single: bool = expected.windows(2).all(|x| x[1] == x[0] + 1);
+
+                assert_eq!(a.union(&b).is_some(), is_single, "{a} and {b}");
+                if let Some(union) = a.union(&b) {
+                    assert_eq!(values(&[union]), expected, "{a} and {b}");
+                }
+            }
+        }
+
+        let (low, high): (Interval<u8>, Interval<u8>) =
+            (Interval::new(0, 254).unwrap(), Interval::new(255, 255).unwrap());
+        assert_eq!(low.union(&high), Interval::new(0, 255));
+        assert_eq!(high.union(&low), Interval::new(0, 255));
+    }
+
+    #[test]
+    fn length_counts_both_bounds() {
+        assert_eq!(Interval::new(0u8, 255).unwrap().length(), 256);
+        assert_eq!(Interval::new(-5i32, -3).unwrap().length(), 3);
+        assert_eq!(Interval::new(7u32, 7).unwrap().length(), 1);
+        assert_eq!(Interval::new(i8::MIN, i8::MAX).unwrap().length(), 256);
+        assert_eq!(Interval::new(i64::MIN, i64::MAX).unwrap().length(), u64::MAX);
+        assert_eq!(Interval::new(0, u64::MAX).unwrap().length(), u64::MAX);
+    }
+
+    #[test]
+    fn parses_negative_bounds() {
+        assert_eq!("-5--3".parse(), Ok(Interval::new(-5i32, -3).unwrap()));
+        assert_eq!("-5-3".parse(), Ok(Interval::new(-5i32, 3).unwrap()));
+        assert_eq!("2 - 4".parse(), Ok(Interval::new(2i32, 4).unwrap()));
+        assert_eq!("0-255".parse(), Ok(Interval::new(0u8, 255).unwrap()));
+        assert_eq!("-128-127".parse(), Ok(Interval::new(i8::MIN, i8::MAX).unwrap()));
+    }
+
+    #[test]
+    fn rejects_invalid_intervals() {
+        let parse = |string: &str| string.parse::<Interval<i32>>();
+
+        assert_eq!(parse("-3--5"), Err(String::from("the interval ends before it starts")));
+        assert_eq!(parse("4-2"), Err(String::from("the interval ends before it starts")));
+        assert_eq!(parse("4"), Err(String::from("expected <start>-<end>")));
+        assert_eq!(parse("-4"), Err(String::from("expected <start>-<end>")));
+        assert!(parse("a-4").unwrap_err().starts_with("invalid start"));
+        assert!(parse("4-").unwrap_err().starts_with("invalid end"));
+        assert!("0-256".parse::<Interval<u8>>().unwrap_err().starts_with("invalid end"));
+        assert!("-1-2".parse::<Interval<u8>>().unwrap_err().starts_with("invalid start"));
+    }
+
+    // Values of a set of small intervals, as a bitmask
+    fn mask(intervals: &[Interval<u8>]) -> u32 {
+        intervals.iter().flat_map(|x| x.start..=x.end).fold(0, |mask, x| mask | 1 << x)
+    }
+
+    fn check_set(set: &IntervalSet<u8>, expected: u32, context: &str) {
+        assert_eq!(mask(set.intervals()), expected, "{context}");
+        assert_eq!(set.length(), u64::from(expected.count_ones()), "{context}");
+        assert!(
+            set.intervals().windows(2).all(|x| x[0].end.successor() < Some(x[1].start)),
+            "{context}: {set:?} isn't sorted, disjoint and non-adjacent"
+        );
+    }
+
+    #[test]
+    fn interval_sets_match_brute_force() {
+        // Small linear congruential generator, so the sets are the same on every run
+        let mut seed: u64 = 2022;
+        let mut next = |bound: u8| -> u8 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((seed >> 33) % u64::from(bound)) as u8
+        };
+        let mut random_intervals = |n: u8| -> Vec<Interval<u8>> {
+            (0..next(n)).map(|_| {
+                let (a, b): (u8, u8) = (next(20), next(20));
+                Interval::new(a.min(b), a.max(b)).unwrap()
+            }).collect()
+        };
+
+        for _ in 0..500 {
+            let (list_a, list_b): (Vec<Interval<u8>>, Vec<Interval<u8>>) =
+                (random_intervals(6), random_intervals(6));
+            let context: String = format!("{list_a:?} and {list_b:?}");
+
+            let mut set_a: IntervalSet<u8> = IntervalSet::new();
+            list_a.iter().for_each(|x| set_a.insert(*x));
+            let set_b: IntervalSet<u8> = list_b.iter().copied().collect();
+            check_set(&set_a, mask(&list_a), &context);
+            check_set(&set_b, mask(&list_b), &context);
+
+            let (mask_a, mask_b): (u32, u32) = (mask(&list_a), mask(&list_b));
+            check_set(&set_a.union(&set_b), mask_a | mask_b, &context);
+            check_set(&set_a.intersection(&set_b), mask_a & mask_b, &context);
+            (0..20).for_each(|x| assert_eq!(set_a.contains_value(&x), mask_a >> x & 1 == 1));
+
+            let universe: Interval<u8> = random_intervals(2).pop()
+                .unwrap_or(Interval { start: 0, end: 19 });
+            let complement: IntervalSet<u8> = set_a.complement(&universe);
+            let expected: u32 = mask(&[universe]) & !mask_a;
+            check_set(&complement, expected, &format!("{context} in {universe}"));
+
+            let all: Vec<Interval<u8>> = list_a.iter().chain(list_b.iter()).copied().collect();
+            for k in 1..=4 {
+                let expected: u32 = (0..20)
+                    .filter(|x| all.iter().filter(|y| y.contains_value(x)).count() >= k)
+                    .fold(0, |mask, x| mask | 1 << x);
+                let covered: IntervalSet<u8> = IntervalSet::covered_at_least(&all, k);
+                check_set(&covered, expected, &format!("{context} at least {k} times"));
+            }
+        }
+    }
+
+    #[test]
+    fn complement_at_the_bounds_of_the_type() {
+        let full: Interval<u8> = Interval::new(0, 255).unwrap();
+        let set = |intervals: &[(u8, u8)]| -> IntervalSet<u8> {
+            intervals.iter().map(|(start, end)| Interval::new(*start, *end).unwrap()).collect()
+        };
+
+        assert_eq!(set(&[]).complement(&full), set(&[(0, 255)]));
+        assert_eq!(set(&[(0, 255)]).complement(&full), set(&[]));
+        assert_eq!(set(&[(0, 0), (255, 255)]).complement(&full), set(&[(1, 254)]));
+        assert_eq!(set(&[(1, 254)]).complement(&full), set(&[(0, 0), (255, 255)]));
+        assert_eq!(set(&[(0, 3), (4, 9)]).intervals(), set(&[(0, 9)]).intervals());
+    }
+}
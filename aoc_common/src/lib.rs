@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::process;
+use std::str::FromStr;
+
+mod interval;
+mod repl;
+
+pub use interval::{Discrete, Interval, IntervalSet};
+pub use repl::run_repl;
+
+
+/// A group of consecutive non-blank lines from an input file.
+#[derive(Debug, Clone)]
+pub struct Record<'a> {
+    /// 1-based number of the first line of the record in the input
+    pub first_line: usize,
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Record<'a> {
+    /// 1-based line numbers covered by the record, end exclusive.
+    pub fn line_numbers(&self) -> Range<usize> {
+        self.first_line..self.first_line + self.lines.len()
+    }
+
+    /// The record as it appeared in the input, without the trailing newline.
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Parses every (trimmed) line of the record, failing on the first invalid one.
+    pub fn parse<T>(&self) -> Result<Vec<T>, LineError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.lines.iter().zip(self.line_numbers()).map(|(line, line_number)| {
+            line.trim().parse::<T>().map_err(|error| LineError {
+                line: line_number,
+                content: String::from(*line),
+                reason: error.to_string(),
+            })
+        }).collect()
+    }
+}
+
+
+/// An input line that could not be parsed.
+#[derive(Debug, Clone)]
+pub struct LineError {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub content: String,
+    pub reason: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {} (found '{}')", self.line, self.reason, self.content)
+    }
+}
+
+impl Error for LineError {}
+
+
+/// Prints the message to stderr and ends the program with a non-zero exit code.
+pub fn exit_with_error(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
+
+
+/// Splits the input into records separated by blank lines.
+///
+/// Lines with only whitespace count as blank, runs of blank lines are a single
+/// separator and the last record is kept even if the input doesn't end with one.
+pub fn split_records(input: &str) -> Vec<Record<'_>> {
+    let mut records: Vec<Record> = Vec::new();
+    let mut current: Option<Record> = None;
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+
+        current.get_or_insert(Record { first_line: i + 1, lines: Vec::new() }).lines.push(line);
+    }
+
+    records.extend(current);
+    records
+}
@@ -0,0 +1,41 @@
+use std::io::{self, BufRead, Write};
+
+
+/// Reads commands from stdin until `quit`, `exit` or the end of the input.
+///
+/// Every non-blank line is split in words and given to `handle`, which returns whether it
+/// knew the command. `help` lists `commands`, pairs of usage and description where an empty
+/// usage continues the previous description, followed by `quit`.
+pub fn run_repl<F>(prompt: &str, commands: &[(&str, &str)], mut handle: F)
+where
+    F: FnMut(&[&str]) -> bool,
+{
+    let commands: Vec<(&str, &str)> = commands.iter().copied()
+        .chain([("quit", "leave the REPL")])
+        .collect();
+    let width: usize = commands.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+
+    print_prompt(prompt);
+    for line in io::stdin().lock().lines() {
+        let line: String = line.expect("Unable to read from stdin...");
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => {},
+            ["help"] => {
+                commands.iter().for_each(|(usage, text)| println!("{usage:<width$}   {text}"));
+            },
+            ["quit"] | ["exit"] => break,
+            _ if handle(&words) => {},
+            _ => println!("Unknown command '{line}'. Type 'help' for the available commands."),
+        }
+
+        print_prompt(prompt);
+    }
+}
+
+
+fn print_prompt(prompt: &str) {
+    print!("{prompt}> ");
+    io::stdout().flush().expect("Unable to write to stdout...");
+}
@@ -1,31 +1,46 @@
+use std::env;
 use std::fs;
+use std::io;
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use aoc_common::{exit_with_error, LineError, split_records};
 
 const INPUT_FILE_PATH: &str = "input";
 const N_OF_ELEMENTS: usize = 3;
 
+const REPORT_PERCENTILES: [usize; 5] = [10, 25, 50, 75, 90];
+const HISTOGRAM_BINS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 50;
 
-fn main() {
-    let input: String = read_input_file(INPUT_FILE_PATH);
+const RANKING_COLUMN_WIDTH: usize = 28;
 
-    let mut calories_greatest: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
-    let mut calories_current: usize = 0;
 
-    for line in input.lines() {
-        let calories: usize = match line.trim().parse() {
-            Ok(value) => value,
-            Err(_) => {
-                update_heap(&mut calories_greatest, calories_current);
-                calories_current = 0;
-                0
-            },
-        };
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|x| x.as_str()) == Some("files") {
+        return print_files_rankings(&args[2..]);
+    }
+
+    let input: String = read_input_file(INPUT_FILE_PATH);
+    let elves: Vec<Elf> = match get_elves(&input) {
+        Ok(elves) => elves,
+        Err(error) => exit_with_error(&format!("Invalid input at {error}")),
+    };
 
-        calories_current += calories;
+    match args.get(1).map(|x| x.as_str()) {
+        Some("repl") => return run_repl(&elves),
+        Some("report") => return print_report(&elves),
+        Some("top") => return print_top_elves(&elves, &args[2..]),
+        _ => {},
     }
 
-    let calories_greatest_sum: usize = calories_greatest.iter().map(|x| x.0).sum();
+    let calories_greatest_sum: usize = get_top_elves(&elves, N_OF_ELEMENTS, TiePolicy::InputOrder)
+        .iter().map(|elf| elf.calories).sum();
 
     println!(
         "The {N_OF_ELEMENTS} elves with the most calories have, \
@@ -43,10 +58,383 @@ fn read_input_file(file_path: &str) -> String {
 }
 
 
-fn update_heap(heap: &mut BinaryHeap<Reverse<usize>>, value: usize) -> () {
+#[derive(Debug, Clone)]
+struct Elf {
+    index: usize,
+    lines: Range<usize>,  // 1-based, end exclusive
+    calories: usize,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TiePolicy {
+    // Keep every elf tied with the k-th one, possibly returning more than k elves
+    IncludeAll,
+    // Exactly k elves; among tied ones, the earliest in the input wins
+    InputOrder,
+}
+
+impl TiePolicy {
+    fn from_name(name: &str) -> Option<TiePolicy> {
+        match name {
+            "all" => Some(TiePolicy::IncludeAll),
+            "order" => Some(TiePolicy::InputOrder),
+            _ => None,
+        }
+    }
+}
+
+
+fn get_elves(input: &str) -> Result<Vec<Elf>, LineError> {
+    split_records(input).iter().enumerate().map(|(index, record)| {
+        let calories: Vec<usize> = record.parse()?;
+        Ok(Elf { index, lines: record.line_numbers(), calories: calories.iter().sum() })
+    }).collect()
+}
+
+
+fn get_top_elves(elves: &[Elf], k: usize, tie_policy: TiePolicy) -> Vec<&Elf> {
+    // Ties are ordered so that later elves are the "smallest" and get popped first
+    let mut heap: BinaryHeap<Reverse<(usize, Reverse<usize>)>> = BinaryHeap::new();
+    elves.iter().for_each(|elf| update_heap(&mut heap, (elf.calories, Reverse(elf.index)), k));
+
+    // Sorting the Reverse values ascending gives the calories in descending order
+    let mut top_elves: Vec<&Elf> = heap.into_sorted_vec().into_iter()
+        .map(|Reverse((_, Reverse(index)))| &elves[index])
+        .collect();
+
+    if let (TiePolicy::IncludeAll, Some(last)) = (tie_policy, top_elves.last()) {
+        let (threshold, last_index): (usize, usize) = (last.calories, last.index);
+        top_elves.extend(
+            elves.iter().filter(|elf| elf.calories == threshold && elf.index > last_index)
+        );
+    }
+
+    top_elves
+}
+
+
+fn update_heap<T: Ord>(heap: &mut BinaryHeap<Reverse<T>>, value: T, n: usize) {
     // Code from https://users.rust-lang.org/ forum
     heap.push(Reverse(value));
-    if heap.len() > N_OF_ELEMENTS {
+    if heap.len() > n {
         heap.pop();
     }
 }
+
+
+fn print_top_elves(elves: &[Elf], args: &[String]) {
+    let k: usize = match args.first().map(|x| x.parse::<usize>()) {
+        Some(Ok(k)) => k,
+        None => N_OF_ELEMENTS,
+        Some(Err(_)) => exit_with_error("Usage: top [k] [--ties all|order]"),
+    };
+    let tie_policy: TiePolicy = match args.get(1).map(|x| x.as_str()) {
+        None => TiePolicy::InputOrder,
+        Some("--ties") => match args.get(2).and_then(|x| TiePolicy::from_name(x)) {
+            Some(tie_policy) => tie_policy,
+            None => exit_with_error("Usage: top [k] [--ties all|order]"),
+        },
+        Some(_) => exit_with_error("Usage: top [k] [--ties all|order]"),
+    };
+
+    for (rank, elf) in get_top_elves(elves, k, tie_policy).iter().enumerate() {
+        println!("{}", format_elf(rank + 1, elf));
+    }
+}
+
+
+fn format_elf(rank: usize, elf: &Elf) -> String {
+    format!(
+        "#{rank} elf {} (lines {}-{}): {} cal.",
+        elf.index, elf.lines.start, elf.lines.end.saturating_sub(1), elf.calories
+    )
+}
+
+
+fn print_files_rankings(args: &[String]) {
+    let usage: &str = "Usage: files <file or directory>... [--top k]";
+    let mut k: usize = N_OF_ELEMENTS;
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--top" => match args_iter.next().map(|x| x.parse::<usize>()) {
+                Some(Ok(value)) => k = value,
+                _ => exit_with_error(usage),
+            },
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let files: Vec<PathBuf> = match expand_directories(&paths) {
+        Ok(files) if !files.is_empty() => files,
+        Ok(_) => exit_with_error(usage),
+        Err(error) => exit_with_error(&error.to_string()),
+    };
+
+    let files_top_elves: Vec<Vec<Elf>> = match get_files_top_elves(&files, k) {
+        Ok(files_top_elves) => files_top_elves,
+        Err(error) => exit_with_error(&error.to_string()),
+    };
+    let global_top_elves: Vec<(usize, &Elf)> = merge_top_elves(&files_top_elves, k);
+
+    let file_names: Vec<String> = files.iter()
+        .map(|file| file.display().to_string())
+        .collect();
+    let mut header: Vec<String> = file_names.clone();
+    header.push(String::from("global"));
+    print_ranking_row(&header);
+
+    for rank in 0..k {
+        let mut row: Vec<String> = files_top_elves.iter()
+            .map(|top_elves| match top_elves.get(rank) {
+                Some(elf) => format!("#{} elf {}: {}", rank + 1, elf.index, elf.calories),
+                None => String::new(),
+            })
+            .collect();
+        row.push(match global_top_elves.get(rank) {
+            Some((file, elf)) => format!(
+                "#{} {} elf {}: {}", rank + 1, file_names[*file], elf.index, elf.calories
+            ),
+            None => String::new(),
+        });
+        print_ranking_row(&row);
+    }
+
+    let global_sum: usize = global_top_elves.iter().map(|(_, elf)| elf.calories).sum();
+    println!();
+    println!(
+        "The {k} elves with the most calories across all files have, \
+        in total, {global_sum} cal."
+    );
+}
+
+
+fn expand_directories(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        // Sorted so that the output doesn't depend on the directory listing order
+        let mut directory_files: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        directory_files.retain(|file| file.is_file());
+        directory_files.sort();
+        files.append(&mut directory_files);
+    }
+
+    Ok(files)
+}
+
+
+// Top elves of a file, with the position of the file in the list
+type FileTopElves = (usize, Result<Vec<Elf>, String>);
+
+
+fn get_files_top_elves(files: &[PathBuf], k: usize) -> Result<Vec<Vec<Elf>>, String> {
+    // A worker per core takes the next unread file until there are none left; each file
+    // only keeps its own top k elves
+    let n_workers: usize = thread::available_parallelism().map_or(1, |n| n.get()).min(files.len());
+    let next_file: AtomicUsize = AtomicUsize::new(0);
+
+    let mut results: Vec<FileTopElves> = thread::scope(|scope| {
+        let handles: Vec<thread::ScopedJoinHandle<Vec<FileTopElves>>> = (0..n_workers)
+            .map(|_| scope.spawn(|| {
+                let mut results: Vec<FileTopElves> = Vec::new();
+                loop {
+                    let i: usize = next_file.fetch_add(1, Ordering::Relaxed);
+                    let Some(file) = files.get(i) else {
+                        break results;
+                    };
+                    results.push((i, get_file_top_elves(file, k)));
+                }
+            }))
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    });
+
+    // Back in the order of the files, so the first invalid one is the one reported
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+
+fn get_file_top_elves(file: &PathBuf, k: usize) -> Result<Vec<Elf>, String> {
+    let input: String = fs::read_to_string(file)
+        .map_err(|error| format!("Unable to read {}: {error}", file.display()))?;
+    let elves: Vec<Elf> = get_elves(&input)
+        .map_err(|error| format!("Invalid input at {}, {error}", file.display()))?;
+
+    Ok(get_top_elves(&elves, k, TiePolicy::InputOrder).into_iter().cloned().collect())
+}
+
+
+// Calories, then (file, elf) position so that earlier ones win ties
+type MergeKey = (usize, Reverse<(usize, usize)>);
+
+
+fn merge_top_elves(files_top_elves: &[Vec<Elf>], k: usize) -> Vec<(usize, &Elf)> {
+    let mut heap: BinaryHeap<Reverse<MergeKey>> = BinaryHeap::new();
+    for (file, top_elves) in files_top_elves.iter().enumerate() {
+        for elf in top_elves {
+            update_heap(&mut heap, (elf.calories, Reverse((file, elf.index))), k);
+        }
+    }
+
+    heap.into_sorted_vec().into_iter()
+        .map(|Reverse((_, Reverse((file, index))))| {
+            let elf: &Elf = files_top_elves[file].iter().find(|elf| elf.index == index).unwrap();
+            (file, elf)
+        })
+        .collect()
+}
+
+
+fn print_ranking_row(columns: &[String]) {
+    let row: Vec<String> = columns.iter()
+        .map(|column| format!("{column:<RANKING_COLUMN_WIDTH$}"))
+        .collect();
+    println!("{}", row.join(" | ").trim_end());
+}
+
+
+fn print_report(elves: &[Elf]) {
+    if elves.is_empty() {
+        println!("No elves were found in the input.");
+        return;
+    }
+
+    let elves_calories: Vec<usize> = elves.iter().map(|elf| elf.calories).collect();
+    let ranks: Vec<usize> = compute_ranks(&elves_calories);
+    let greatest: Vec<&Elf> = get_top_elves(elves, N_OF_ELEMENTS, TiePolicy::InputOrder);
+
+    let mut sorted: Vec<usize> = elves_calories.clone();
+    sorted.sort_unstable();
+    let mean: f64 = sorted.iter().sum::<usize>() as f64 / sorted.len() as f64;
+    let median: f64 = compute_median(&sorted);
+
+    println!("{:>5} {:>13} {:>10} {:>6}", "Elf", "Lines", "Calories", "Rank");
+    for elf in elves {
+        let lines: String = format!("{}-{}", elf.lines.start, elf.lines.end.saturating_sub(1));
+        println!("{:>5} {lines:>13} {:>10} {:>6}", elf.index, elf.calories, ranks[elf.index]);
+    }
+
+    println!();
+    println!("Elves: {}", sorted.len());
+    println!("Min: {}  Max: {}", sorted[0], sorted[sorted.len() - 1]);
+    println!("Mean: {mean:.2}  Median: {median:.1}");
+    for p in REPORT_PERCENTILES {
+        println!("P{p}: {}", compute_percentile(&sorted, p));
+    }
+
+    println!();
+    print_histogram(&sorted);
+
+    println!();
+    println!("Part 1: {}", format_elf(1, greatest[0]));
+    println!(
+        "Part 2: the {N_OF_ELEMENTS} elves with the most calories have, \
+        in total, {} cal.", greatest.iter().map(|elf| elf.calories).sum::<usize>()
+    );
+}
+
+
+fn compute_ranks(elves_calories: &[usize]) -> Vec<usize> {
+    // Tied elves share the best rank among them, e.g. 1, 2, 2, 4
+    let mut sorted: Vec<usize> = elves_calories.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+    elves_calories.iter()
+        .map(|calories| sorted.partition_point(|x| x > calories) + 1)
+        .collect()
+}
+
+
+fn compute_median(sorted: &[usize]) -> f64 {
+    let middle: usize = sorted.len() / 2;
+
+    if sorted.len().is_multiple_of(2) {
+        return (sorted[middle - 1] + sorted[middle]) as f64 / 2.0;
+    }
+
+    sorted[middle] as f64
+}
+
+
+fn compute_percentile(sorted: &[usize], percentile: usize) -> usize {
+    // Nearest-rank method: smallest value with at least `percentile`% of values up to it
+    let rank: usize = (percentile * sorted.len()).div_ceil(100);
+    sorted[rank.max(1) - 1]
+}
+
+
+fn print_histogram(sorted: &[usize]) {
+    let (min, max): (usize, usize) = (sorted[0], sorted[sorted.len() - 1]);
+    let bin_width: usize = ((max - min) / HISTOGRAM_BINS).max(1) + 1;
+
+    let mut bins: Vec<usize> = vec![0; HISTOGRAM_BINS];
+    for calories in sorted {
+        bins[((calories - min) / bin_width).min(HISTOGRAM_BINS - 1)] += 1;
+    }
+
+    let largest_bin: usize = *bins.iter().max().unwrap();
+    for (i, count) in bins.iter().enumerate() {
+        let bin_start: usize = min + i * bin_width;
+        let bar: String = "#".repeat(count * HISTOGRAM_WIDTH / largest_bin);
+        println!(
+            "{bin_start:>7} - {:>7} | {bar} {count}", bin_start + bin_width - 1
+        );
+    }
+}
+
+
+fn run_repl(elves: &[Elf]) {
+    println!("Loaded {} elves. Type 'help' for the available commands.", elves.len());
+
+    let commands: [(&str, &str); 3] = [
+        ("top <n> [all|order]", "the <n> elves with the most calories, keeping"),
+        ("", "every tied elf (all) or the earliest (order)"),
+        ("elf <i>", "calories carried by the <i>-th elf (0-indexed)"),
+    ];
+    aoc_common::run_repl("day01", &commands, |words| {
+        match words {
+            ["top", n] | ["top", n, _] => {
+                let tie_policy: Option<TiePolicy> = match words.get(2) {
+                    Some(name) => TiePolicy::from_name(name),
+                    None => Some(TiePolicy::InputOrder),
+                };
+
+                match (n.parse::<usize>(), tie_policy) {
+                    (Ok(n), Some(tie_policy)) => {
+                        let top_elves: Vec<&Elf> = get_top_elves(elves, n, tie_policy);
+                        for (rank, elf) in top_elves.iter().enumerate() {
+                            println!("{}", format_elf(rank + 1, elf));
+                        }
+                        let sum: usize = top_elves.iter().map(|elf| elf.calories).sum();
+                        println!("Sum: {sum} cal.");
+                    },
+                    (Err(_), _) => println!("'{n}' is not a valid number of elves."),
+                    (_, None) => println!("The tie policy must be 'all' or 'order'."),
+                }
+            },
+            ["elf", i] => match i.parse::<usize>().ok().and_then(|i| elves.get(i)) {
+                Some(elf) => println!(
+                    "Elf {i} carries {} cal. (lines {}-{})",
+                    elf.calories, elf.lines.start, elf.lines.end.saturating_sub(1)
+                ),
+                None => println!("There is no elf {i}."),
+            },
+            _ => return false,
+        }
+
+        true
+    });
+}
@@ -0,0 +1,190 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::game::Game;
+
+const PROBABILITY_TOLERANCE: f64 = 1e-6;
+
+
+// Opponent whose shape depends only on its previous one; an opponent that draws every
+// shape from the same distribution is the special case where all rows are equal
+#[derive(Debug, Clone)]
+pub struct OpponentModel {
+    pub initial: Vec<f64>,
+    pub transitions: Vec<Vec<f64>>,
+}
+
+impl OpponentModel {
+    pub fn from_distribution(distribution: Vec<f64>) -> OpponentModel {
+        OpponentModel {
+            transitions: vec![distribution.clone(); distribution.len()],
+            initial: distribution,
+        }
+    }
+
+    // `A=0.5,B=0.3,C=0.2`, with opponent codes; missing shapes have probability zero
+    pub fn parse_distribution(game: &Game, spec: &str) -> Result<Vec<f64>, String> {
+        let mut distribution: Vec<f64> = vec![0.0; game.shapes.len()];
+
+        for entry in spec.split([',', ' ']).filter(|x| !x.is_empty()) {
+            let (code, probability) = entry.split_once('=')
+                .ok_or_else(|| format!("Expected <code>=<probability>, found '{entry}'"))?;
+            let shape: usize = game.get_opponent_shape(code)
+                .ok_or_else(|| format!("Unknown opponent code '{code}'"))?.index;
+            distribution[shape] = probability.parse::<f64>().ok()
+                .filter(|x| (0.0..=1.0).contains(x))
+                .ok_or_else(|| format!("Invalid probability '{probability}'"))?;
+        }
+
+        if (distribution.iter().sum::<f64>() - 1.0).abs() > PROBABILITY_TOLERANCE {
+            return Err(format!("The probabilities in '{spec}' don't add up to 1"));
+        }
+
+        Ok(distribution)
+    }
+
+    // One line per previous shape, `<code> <distribution>`, plus an optional
+    // `start <distribution>` line (uniform by default); `#` starts a comment
+    pub fn parse_markov(game: &Game, table: &str) -> Result<OpponentModel, String> {
+        let n_shapes: usize = game.shapes.len();
+        let mut initial: Vec<f64> = vec![1.0 / n_shapes as f64; n_shapes];
+        let mut transitions: Vec<Option<Vec<f64>>> = vec![None; n_shapes];
+
+        for (i, line) in table.lines().enumerate() {
+            let line: &str = line.split('#').next().unwrap_or("").trim();
+            let Some((from, spec)) = line.split_once(char::is_whitespace) else {
+                if line.is_empty() {
+                    continue;
+                }
+                return Err(format!("Line {}: expected '<code> <distribution>'", i + 1));
+            };
+
+            let distribution: Vec<f64> = OpponentModel::parse_distribution(game, spec)
+                .map_err(|error| format!("Line {}: {error}", i + 1))?;
+            match from {
+                "start" => initial = distribution,
+                _ => match game.get_opponent_shape(from) {
+                    Some(shape) => transitions[shape.index] = Some(distribution),
+                    None => return Err(format!("Line {}: unknown opponent code '{from}'", i + 1)),
+                },
+            }
+        }
+
+        let transitions: Vec<Vec<f64>> = transitions.into_iter().enumerate()
+            .map(|(shape, row)| row.ok_or_else(|| {
+                format!("Missing the transitions from '{}'", game.shapes[shape].opponent_code)
+            }))
+            .collect::<Result<Vec<Vec<f64>>, String>>()?;
+
+        Ok(OpponentModel { initial, transitions })
+    }
+
+    pub fn fit_frequency(history: &[usize], n_shapes: usize) -> OpponentModel {
+        OpponentModel::from_distribution(get_frequencies(history.iter(), n_shapes))
+    }
+
+    // Shapes never followed by anything fall back to the overall frequencies
+    pub fn fit_markov(history: &[usize], n_shapes: usize) -> OpponentModel {
+        let frequencies: Vec<f64> = get_frequencies(history.iter(), n_shapes);
+        let transitions: Vec<Vec<f64>> = (0..n_shapes).map(|from| {
+            let next: Vec<&usize> = history.windows(2)
+                .filter(|pair| pair[0] == from)
+                .map(|pair| &pair[1])
+                .collect();
+            match next.is_empty() {
+                true => frequencies.clone(),
+                false => get_frequencies(next.into_iter(), n_shapes),
+            }
+        }).collect();
+
+        OpponentModel { initial: frequencies, transitions }
+    }
+
+    fn sample(rng: &mut StdRng, distribution: &[f64]) -> usize {
+        let mut remaining: f64 = rng.gen::<f64>();
+        for (shape, probability) in distribution.iter().enumerate() {
+            remaining -= probability;
+            if remaining < 0.0 {
+                return shape;
+            }
+        }
+
+        // Rounding errors can leave a tiny remainder; give it to the last possible shape
+        distribution.iter().rposition(|x| *x > 0.0).unwrap_or(0)
+    }
+}
+
+
+fn get_frequencies<'a>(shapes: impl Iterator<Item = &'a usize>, n_shapes: usize) -> Vec<f64> {
+    let mut counts: Vec<f64> = vec![0.0; n_shapes];
+    shapes.for_each(|shape| counts[*shape] += 1.0);
+
+    let total: f64 = counts.iter().sum();
+    match total {
+        0.0 => vec![1.0 / n_shapes as f64; n_shapes],
+        _ => counts.iter().map(|x| x / total).collect(),
+    }
+}
+
+
+// `round_scores[i][s]` is what the guide scores in round `i` if the opponent plays `s`.
+// Forward pass over the chain keeping, for each opponent shape at the current round, its
+// probability and the first two moments of the accumulated score restricted to it.
+pub fn compute_expected_score(model: &OpponentModel, round_scores: &[Vec<f64>]) -> (f64, f64) {
+    let n_shapes: usize = model.initial.len();
+    let mut probability: Vec<f64> = vec![0.0; n_shapes];
+    let mut first_moment: Vec<f64> = vec![0.0; n_shapes];
+    let mut second_moment: Vec<f64> = vec![0.0; n_shapes];
+
+    for (i, scores) in round_scores.iter().enumerate() {
+        let mut next_probability: Vec<f64> = vec![0.0; n_shapes];
+        let mut next_first: Vec<f64> = vec![0.0; n_shapes];
+        let mut next_second: Vec<f64> = vec![0.0; n_shapes];
+
+        for to in 0..n_shapes {
+            let (p, m, q): (f64, f64, f64) = match i {
+                0 => (model.initial[to], 0.0, 0.0),
+                _ => (0..n_shapes).fold((0.0, 0.0, 0.0), |(p, m, q), from| {
+                    let transition: f64 = model.transitions[from][to];
+                    (
+                        p + probability[from] * transition,
+                        m + first_moment[from] * transition,
+                        q + second_moment[from] * transition,
+                    )
+                }),
+            };
+
+            let x: f64 = scores[to];
+            next_probability[to] = p;
+            next_first[to] = m + p * x;
+            next_second[to] = q + 2.0 * x * m + p * x * x;
+        }
+
+        probability = next_probability;
+        first_moment = next_first;
+        second_moment = next_second;
+    }
+
+    let mean: f64 = first_moment.iter().sum();
+    let variance: f64 = second_moment.iter().sum::<f64>() - mean * mean;
+    (mean, variance.max(0.0))
+}
+
+
+pub fn simulate_scores(
+    model: &OpponentModel, round_scores: &[Vec<f64>], n_samples: usize, rng: &mut StdRng
+) -> Vec<f64> {
+    (0..n_samples).map(|_| {
+        let mut shape: usize = OpponentModel::sample(rng, &model.initial);
+        let mut score: f64 = 0.0;
+
+        for (i, scores) in round_scores.iter().enumerate() {
+            if i > 0 {
+                shape = OpponentModel::sample(rng, &model.transitions[shape]);
+            }
+            score += scores[shape];
+        }
+
+        score
+    }).collect()
+}
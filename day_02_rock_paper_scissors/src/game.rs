@@ -0,0 +1,248 @@
+use std::fs;
+
+use aoc_common::LineError;
+
+pub const VICTORY_POINTS: usize = 6;
+pub const TIE_POINTS: usize = 3;
+pub const DEFEAT_POINTS: usize = 0;
+
+pub const DEFAULT_SCORING: Scoring = Scoring {
+    victory: VICTORY_POINTS,
+    tie: TIE_POINTS,
+    defeat: DEFEAT_POINTS,
+};
+const DEFAULT_OUTCOME_CODES: [(&str, Outcome); 3] = [
+    ("X", Outcome::Defeat),
+    ("Y", Outcome::Tie),
+    ("Z", Outcome::Victory),
+];
+
+// Shapes are listed in cyclic order: each one beats the (n - 1) / 2 shapes before it
+pub const PRESETS: [(&str, &str); 3] = [
+    ("rps", "\
+        shape Rock A X 1\n\
+        shape Paper B Y 2\n\
+        shape Scissors C Z 3\n\
+    "),
+    ("rpsls", "\
+        shape Rock A V 1\n\
+        shape Spock B W 2\n\
+        shape Paper C X 3\n\
+        shape Lizard D Y 4\n\
+        shape Scissors E Z 5\n\
+    "),
+    ("rps15", "\
+        shape Gun A a 1\n\
+        shape Lightning B b 2\n\
+        shape Devil C c 3\n\
+        shape Dragon D d 4\n\
+        shape Water E e 5\n\
+        shape Air F f 6\n\
+        shape Paper G g 7\n\
+        shape Sponge H h 8\n\
+        shape Wolf I i 9\n\
+        shape Tree J j 10\n\
+        shape Human K k 11\n\
+        shape Snake L l 12\n\
+        shape Scissors M m 13\n\
+        shape Fire N n 14\n\
+        shape Rock O o 15\n\
+    "),
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Defeat,
+    Tie,
+    Victory,
+}
+
+impl Outcome {
+    fn from_name(name: &str) -> Option<Outcome> {
+        match name {
+            "defeat" => Some(Outcome::Defeat),
+            "tie" => Some(Outcome::Tie),
+            "victory" => Some(Outcome::Victory),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Scoring {
+    pub victory: usize,
+    pub tie: usize,
+    pub defeat: usize,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Shape {
+    pub name: String,
+    pub opponent_code: String,
+    pub player_code: String,
+    pub points: usize,
+    pub index: usize,
+    beats: Vec<usize>,
+}
+
+impl Shape {
+    pub fn get_outcome(&self, shape_opponent: &Shape) -> Outcome {
+        match shape_opponent.index {
+            x if self.beats.contains(&x) => Outcome::Victory,
+            x if x == self.index => Outcome::Tie,
+            _ => Outcome::Defeat,
+        }
+    }
+
+    pub fn get_outcome_points(&self, shape_opponent: &Shape, scoring: &Scoring) -> usize {
+        match self.get_outcome(shape_opponent) {
+            Outcome::Victory => scoring.victory,
+            Outcome::Tie => scoring.tie,
+            Outcome::Defeat => scoring.defeat,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub shapes: Vec<Shape>,
+    pub scoring: Scoring,
+    outcome_codes: Vec<(String, Outcome)>,
+}
+
+impl Game {
+    // Either the name of a preset or the path of a file with a rules table
+    pub fn load(name_or_path: &str) -> Result<Game, String> {
+        match PRESETS.iter().find(|(name, _)| *name == name_or_path) {
+            Some((_, table)) => Game::from_table(table).map_err(|error| error.to_string()),
+            None => {
+                let table: String = fs::read_to_string(name_or_path)
+                    .map_err(|error| format!("Unable to read {name_or_path}: {error}"))?;
+                Game::from_table(&table).map_err(|error| format!("{name_or_path}, {error}"))
+            },
+        }
+    }
+
+    // Each line is either `shape <name> <opponent code> <player code> <points>` or
+    // `outcome <defeat|tie|victory> <points> <code>`; blank lines and `#` comments are skipped
+    pub fn from_table(table: &str) -> Result<Game, LineError> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        let mut scoring: Scoring = DEFAULT_SCORING;
+        let mut outcome_codes: Vec<(String, Outcome)> = DEFAULT_OUTCOME_CODES.iter()
+            .map(|(code, outcome)| (String::from(*code), *outcome))
+            .collect();
+        let mut outcome_lines: Vec<(Outcome, usize)> = Vec::new();
+
+        let lines: Vec<&str> = table.lines().collect();
+        let error = |line: usize, reason: &str| LineError {
+            line: line + 1,
+            content: String::from(*lines.get(line).unwrap_or(&"")),
+            reason: String::from(reason),
+        };
+
+        for (i, line) in lines.iter().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {},
+                [comment, ..] if comment.starts_with('#') => {},
+                ["shape", name, opponent_code, player_code, points] => {
+                    let duplicated: bool = shapes.iter().any(|x| {
+                        x.opponent_code == *opponent_code || x.player_code == *player_code
+                    });
+                    if duplicated {
+                        return Err(error(i, "another shape already uses this code"));
+                    }
+
+                    shapes.push(Shape {
+                        name: String::from(*name),
+                        opponent_code: String::from(*opponent_code),
+                        player_code: String::from(*player_code),
+                        points: points.parse().map_err(|_| error(i, "invalid shape points"))?,
+                        index: shapes.len(),
+                        beats: Vec::new(),
+                    });
+                },
+                ["outcome", name, points, code] => {
+                    let outcome: Outcome = Outcome::from_name(name)
+                        .ok_or_else(|| error(i, "outcome must be defeat, tie or victory"))?;
+                    let points: usize = points.parse()
+                        .map_err(|_| error(i, "invalid outcome points"))?;
+
+                    match outcome {
+                        Outcome::Victory => scoring.victory = points,
+                        Outcome::Tie => scoring.tie = points,
+                        Outcome::Defeat => scoring.defeat = points,
+                    }
+                    outcome_codes.retain(|(_, x)| *x != outcome);
+                    outcome_codes.push((String::from(*code), outcome));
+                    outcome_lines.retain(|(x, _)| *x != outcome);
+                    outcome_lines.push((outcome, i));
+                },
+                _ => return Err(error(i, "expected a shape or an outcome definition")),
+            }
+        }
+
+        // Checked once every outcome is defined, since a table can swap the default codes; the
+        // later of two outcomes sharing a code always comes from the table
+        for (j, (code, outcome)) in outcome_codes.iter().enumerate() {
+            if outcome_codes[..j].iter().any(|(x, _)| x == code) {
+                let line: usize = outcome_lines.iter().find(|(x, _)| x == outcome)
+                    .map(|(_, line)| *line)
+                    .expect("Only outcomes from the table come after others");
+                return Err(error(line, "another outcome already uses this code"));
+            }
+        }
+
+        let n_shapes: usize = shapes.len();
+        if n_shapes < 3 || n_shapes.is_multiple_of(2) {
+            let last_line: usize = lines.len().saturating_sub(1);
+            return Err(error(last_line, "a cyclic game needs an odd number (3+) of shapes"));
+        }
+
+        for shape in shapes.iter_mut() {
+            shape.beats = (1..=n_shapes / 2)
+                .map(|offset| (shape.index + n_shapes - offset) % n_shapes)
+                .collect();
+        }
+
+        Ok(Game { shapes, scoring, outcome_codes })
+    }
+
+    pub fn get_opponent_shape(&self, code: &str) -> Option<&Shape> {
+        self.shapes.iter().find(|shape| shape.opponent_code == code)
+    }
+
+    pub fn get_player_shape(&self, code: &str) -> Option<&Shape> {
+        self.shapes.iter().find(|shape| shape.player_code == code)
+    }
+
+    pub fn get_outcome(&self, code: &str) -> Option<Outcome> {
+        self.outcome_codes.iter().find(|(x, _)| x == code).map(|(_, outcome)| *outcome)
+    }
+
+    pub fn get_outcome_code(&self, outcome: Outcome) -> &str {
+        self.outcome_codes.iter().find(|(_, x)| *x == outcome).map(|(code, _)| code.as_str())
+            .expect("Every outcome has a code")
+    }
+
+    // With more than three shapes, several of them win (or lose); the closest one is picked
+    pub fn get_shape_for_outcome(&self, shape_opponent: &Shape, outcome: Outcome) -> &Shape {
+        let n_shapes: usize = self.shapes.len();
+        let offset: usize = match outcome {
+            Outcome::Victory => 1,
+            Outcome::Tie => 0,
+            Outcome::Defeat => n_shapes - 1,
+        };
+
+        &self.shapes[(shape_opponent.index + offset) % n_shapes]
+    }
+
+    pub fn get_score(&self, shape_player: &Shape, shape_opponent: &Shape) -> usize {
+        shape_player.points + shape_player.get_outcome_points(shape_opponent, &self.scoring)
+    }
+}
@@ -1,27 +1,97 @@
+use std::env;
 use std::fs;
 use std::str;
 
-const INPUT_FILE_PATH: &str = "input";
+mod analysis;
+mod game;
+mod solver;
+mod tournament;
+
+use aoc_common::exit_with_error;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
-const VICTORY_POINTS: usize = 6;
-const TIE_POINTS: usize = 3;
-const DEFEAT_POINTS: usize = 0;
+use analysis::OpponentModel;
+use game::{Game, Shape};
+use solver::{Constraints, Solution};
+use tournament::{ScoreDistribution, Strategy, STRATEGY_NAMES};
 
-const ROCK: Shape = Shape { encoding: 'A', beats: 'C', loses: 'B', points: 1 };
-const PAPER: Shape = Shape { encoding: 'B', beats: 'A', loses: 'C', points: 2 };
-const SCISSORS: Shape = Shape { encoding: 'C', beats: 'B', loses: 'A', points: 3 };
+const INPUT_FILE_PATH: &str = "input";
+const DEFAULT_GAME: &str = "rps";
+const DEFAULT_MATCHES: usize = 100;
+const DEFAULT_SAMPLES: usize = 1_000;
+
+const USAGE: &str = "Usage: [--game <preset or file>] [--strategy shapes|outcomes]... [--rounds]";
+const TOURNAMENT_USAGE: &str = "Usage: tournament [--game <preset or file>] \
+    [--players <name,...>] [--opponent <name>] [--rounds <n>] [--matches <n>] [--seed <n>]";
+const SOLVE_USAGE: &str = "Usage: solve [--game <preset or file>] [--strategy shapes|outcomes] \
+    [--max-wins <n>] [--max-repeats <n>] [--output <file>]";
+const ANALYZE_USAGE: &str = "Usage: analyze [--game <preset or file>] \
+    [--strategy shapes|outcomes]... (--distribution <code>=<p>,... | --markov <file> | \
+    --fit frequency|markov) [--samples <n>] [--seed <n>]";
 
 
 fn main() {
     let input: String = read_input_file(INPUT_FILE_PATH);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|x| x.as_str()) {
+        Some("tournament") => run_tournament(&input, &args[1..]),
+        Some("solve") => print_optimal_guide(&input, &args[1..]),
+        Some("analyze") => print_expected_scores(&input, &args[1..]),
+        _ => print_guide_scores(&input, &args),
+    }
+}
+
+
+fn print_guide_scores(input: &str, args: &[String]) {
+    let mut game_name: &str = DEFAULT_GAME;
+    let mut strategies: Vec<GuideStrategy> = Vec::new();
+    let mut show_rounds: bool = false;
 
-    let mut score: usize = 0;
-    for line in input.lines() {
-        let (shape_opponent, shape_player): (Shape, Shape) = get_round_shapes(line);
-        score += shape_player.points + shape_player.get_outcome_points(&shape_opponent);
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--game" => match args_iter.next() {
+                Some(name) => game_name = name,
+                None => exit_with_error(USAGE),
+            },
+            "--strategy" => match args_iter.next().and_then(|x| GuideStrategy::from_name(x)) {
+                Some(strategy) => strategies.push(strategy),
+                None => exit_with_error(USAGE),
+            },
+            "--rounds" => show_rounds = true,
+            _ => exit_with_error(USAGE),
+        }
+    }
+
+    if strategies.is_empty() {
+        strategies = GuideStrategy::ALL.to_vec();
     }
 
-    println!("Your total score is {score}.");
+    let game: Game = Game::load(game_name).unwrap_or_else(|error| exit_with_error(&error));
+
+    for strategy in strategies {
+        let mut score: usize = 0;
+        for (i, line) in input.lines().enumerate() {
+            let (shape_opponent, shape_player): (&Shape, &Shape) =
+                get_round_shapes(&game, line, strategy).unwrap_or_else(|| {
+                    exit_with_error(&format!("Invalid round at line {}: '{line}'", i + 1))
+                });
+            let round_score: usize = game.get_score(shape_player, shape_opponent);
+            score += round_score;
+
+            if show_rounds {
+                println!(
+                    "Round {}: {line} -> {} vs {}, {} + {} = {round_score}",
+                    i + 1, shape_opponent.name, shape_player.name, shape_player.points,
+                    shape_player.get_outcome_points(shape_opponent, &game.scoring),
+                );
+            }
+        }
+
+        println!("Your total score with the '{}' strategy is {score}.", strategy.name());
+    }
 }
 
 
@@ -34,50 +104,315 @@ fn read_input_file(file_path: &str) -> String {
 }
 
 
-fn get_round_shapes(input_line: &str) -> (Shape, Shape) {
-    let shapes: Vec<&str> = input_line.split(" ").collect();
-    let opponent_shape: Shape = get_shape_from_encoding(shapes[0]);
-    let player_shape: Shape = get_player_shape(&opponent_shape, shapes[1]);
-    (opponent_shape, player_shape)
+
+fn get_round_shapes<'a>(
+    game: &'a Game, input_line: &str, strategy: GuideStrategy
+) -> Option<(&'a Shape, &'a Shape)> {
+    let codes: Vec<&str> = input_line.split_whitespace().collect();
+    if codes.len() != 2 {
+        return None;
+    }
+
+    let opponent_shape: &Shape = game.get_opponent_shape(codes[0])?;
+    let player_shape: &Shape = strategy.get_player_shape(game, opponent_shape, codes[1])?;
+    Some((opponent_shape, player_shape))
 }
 
 
-struct Shape {
-    encoding: char,
-    beats: char,
-    loses: char,
-    points: usize,
+#[derive(Debug, Clone, Copy)]
+enum GuideStrategy {
+    // Part 1: the second column is the shape to play (X, Y and Z in the puzzle)
+    Shapes,
+    // Part 2: the second column is the outcome to achieve (X, Y and Z = lose, draw and win)
+    Outcomes,
 }
 
-impl Shape {
-    fn get_outcome_points(&self, shape_opponent: &Shape) -> usize {
-        match shape_opponent.encoding {
-            x if x == self.beats => VICTORY_POINTS,
-            x if x == self.encoding => TIE_POINTS,
-            _ => DEFEAT_POINTS,
+impl GuideStrategy {
+    const ALL: [GuideStrategy; 2] = [GuideStrategy::Shapes, GuideStrategy::Outcomes];
+
+    fn from_name(name: &str) -> Option<GuideStrategy> {
+        GuideStrategy::ALL.into_iter().find(|strategy| strategy.name() == name)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            GuideStrategy::Shapes => "shapes",
+            GuideStrategy::Outcomes => "outcomes",
+        }
+    }
+
+    fn get_player_shape<'a>(
+        &self, game: &'a Game, opponent_shape: &'a Shape, code: &str
+    ) -> Option<&'a Shape> {
+        match self {
+            GuideStrategy::Shapes => game.get_player_shape(code),
+            GuideStrategy::Outcomes => game.get_outcome(code)
+                .map(|outcome| game.get_shape_for_outcome(opponent_shape, outcome)),
         }
     }
 }
 
 
-fn get_shape_from_encoding(value: &str) -> Shape {
-    match value {
-        "A" => ROCK,
-        "B" => PAPER,
-        "C" => SCISSORS,
-        _ => todo!(),
+fn run_tournament(input: &str, args: &[String]) {
+    let mut game_name: &str = DEFAULT_GAME;
+    let mut players: Vec<String> = STRATEGY_NAMES.iter().map(|x| String::from(*x)).collect();
+    let mut opponent: Option<String> = None;
+    let mut n_rounds: usize = input.lines().count();
+    let mut n_matches: usize = DEFAULT_MATCHES;
+    let mut seed: u64 = 0;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--game", Some(value)) => { game_name = value; Some(()) },
+            ("--players", Some(value)) => {
+                players = value.split(',').map(String::from).collect();
+                Some(())
+            },
+            ("--opponent", Some(value)) => { opponent = Some(value.clone()); Some(()) },
+            ("--rounds", Some(value)) => value.parse().ok().map(|x| n_rounds = x),
+            ("--matches", Some(value)) => value.parse().ok().map(|x| n_matches = x),
+            ("--seed", Some(value)) => value.parse().ok().map(|x| seed = x),
+            _ => None,
+        };
+
+        if parsed.is_none() || n_matches == 0 {
+            exit_with_error(TOURNAMENT_USAGE);
+        }
     }
-}
 
+    let game: Game = Game::load(game_name).unwrap_or_else(|error| exit_with_error(&error));
 
-fn get_player_shape(opponent_shape: &Shape, strategy: &str) -> Shape {
-    // This strategy is from part 2 of the puzzle
-    let player_encoding: char = match strategy {
-        "X" => opponent_shape.beats,
-        "Y" => opponent_shape.encoding,
-        "Z" => opponent_shape.loses,
-        _ => todo!(),
+    // Everybody against the opponent if there is one, otherwise a round-robin
+    let pairings: Vec<(&String, &String)> = match &opponent {
+        Some(opponent) => players.iter()
+            .filter(|player| *player != opponent)
+            .map(|player| (player, opponent))
+            .collect(),
+        None => players.iter().enumerate()
+            .flat_map(|(i, a)| players[i + 1..].iter().map(move |b| (a, b)))
+            .collect(),
     };
 
-    get_shape_from_encoding(&player_encoding.to_string())
+    println!(
+        "{} pairings, {n_matches} matches of {n_rounds} rounds each (seed {seed})",
+        pairings.len()
+    );
+
+    let mut totals: Vec<(String, Vec<usize>)> = Vec::new();
+    for (name_a, name_b) in pairings {
+        let mut scores_a: Vec<usize> = Vec::with_capacity(n_matches);
+        let mut scores_b: Vec<usize> = Vec::with_capacity(n_matches);
+
+        for m in 0..n_matches {
+            // Different seeds for each match and each side of it
+            let match_seed: u64 = seed.wrapping_add((m as u64).wrapping_mul(2));
+            let mut player_a: Box<dyn Strategy> =
+                tournament::create_strategy(name_a, &game, input, match_seed)
+                    .unwrap_or_else(|error| exit_with_error(&error));
+            let mut player_b: Box<dyn Strategy> =
+                tournament::create_strategy(name_b, &game, input, match_seed.wrapping_add(1))
+                    .unwrap_or_else(|error| exit_with_error(&error));
+
+            let (score_a, score_b): (usize, usize) =
+                tournament::play_match(&game, player_a.as_mut(), player_b.as_mut(), n_rounds);
+            scores_a.push(score_a);
+            scores_b.push(score_b);
+        }
+
+        let wins_a: usize = scores_a.iter().zip(&scores_b).filter(|(a, b)| a > b).count();
+        let wins_b: usize = scores_a.iter().zip(&scores_b).filter(|(a, b)| a < b).count();
+        println!();
+        let ties: usize = n_matches - wins_a - wins_b;
+        println!("{name_a} vs {name_b}: {wins_a} - {wins_b} ({ties} ties)");
+        println!("  {}", format_distribution(name_a, &scores_a));
+        println!("  {}", format_distribution(name_b, &scores_b));
+
+        for (name, scores) in [(name_a, scores_a), (name_b, scores_b)] {
+            match totals.iter_mut().find(|(x, _)| x == name) {
+                Some((_, all_scores)) => all_scores.extend(scores),
+                None => totals.push((name.clone(), scores)),
+            }
+        }
+    }
+
+    let mut ranking: Vec<(String, f64)> = totals.iter()
+        .map(|(name, scores)| (name.clone(), ScoreDistribution::from_scores(scores).mean))
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    println!();
+    println!("Ranking by mean score per match:");
+    for (i, (name, mean)) in ranking.iter().enumerate() {
+        println!("{:>3}. {name:<10} {mean:.1}", i + 1);
+    }
+}
+
+
+fn format_distribution(name: &str, scores: &[usize]) -> String {
+    let distribution: ScoreDistribution = ScoreDistribution::from_scores(scores);
+    format!(
+        "{name:<10} mean {:.1}, sd {:.1}, min {}, median {}, max {}",
+        distribution.mean, distribution.std_dev,
+        distribution.min, distribution.median, distribution.max
+    )
+}
+
+
+fn print_optimal_guide(input: &str, args: &[String]) {
+    let mut game_name: &str = DEFAULT_GAME;
+    let mut strategy: GuideStrategy = GuideStrategy::Shapes;
+    let mut constraints: Constraints = Constraints::default();
+    let mut output: Option<&str> = None;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--game", Some(value)) => { game_name = value; Some(()) },
+            ("--strategy", Some(value)) => GuideStrategy::from_name(value).map(|x| strategy = x),
+            ("--max-wins", Some(value)) => value.parse().ok()
+                .map(|x| constraints.max_wins = Some(x)),
+            ("--max-repeats", Some(value)) => value.parse().ok()
+                .map(|x| constraints.max_repeats = Some(x)),
+            ("--output", Some(value)) => { output = Some(value); Some(()) },
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            exit_with_error(SOLVE_USAGE);
+        }
+    }
+
+    let game: Game = Game::load(game_name).unwrap_or_else(|error| exit_with_error(&error));
+    let opponent: Vec<&Shape> = input.lines().enumerate().map(|(i, line)| {
+        line.split_whitespace().next()
+            .and_then(|code| game.get_opponent_shape(code))
+            .unwrap_or_else(|| {
+                exit_with_error(&format!("Invalid round at line {}: '{line}'", i + 1))
+            })
+    }).collect();
+    let opponent_indexes: Vec<usize> = opponent.iter().map(|shape| shape.index).collect();
+
+    let outcomes_only: bool = matches!(strategy, GuideStrategy::Outcomes);
+    let solution: Solution = solver::solve(&game, &opponent_indexes, constraints, outcomes_only)
+        .unwrap_or_else(|| exit_with_error("No guide satisfies the constraints"));
+
+    let guide: String = opponent.iter().zip(&solution.shapes).map(|(shape_opponent, shape)| {
+        let shape_player: &Shape = &game.shapes[*shape];
+        let code: &str = match strategy {
+            GuideStrategy::Shapes => &shape_player.player_code,
+            GuideStrategy::Outcomes => {
+                game.get_outcome_code(shape_player.get_outcome(shape_opponent))
+            },
+        };
+        format!("{} {code}\n", shape_opponent.opponent_code)
+    }).collect();
+
+    println!(
+        "The best possible score with the '{}' strategy is {} ({} wins).",
+        strategy.name(), solution.score, solution.wins
+    );
+
+    match output {
+        Some(path) => {
+            fs::write(path, guide).unwrap_or_else(|error| {
+                exit_with_error(&format!("Unable to write {path}: {error}"))
+            });
+            println!("The guide was written to {path}.");
+        },
+        None => print!("{guide}"),
+    }
+}
+
+
+fn print_expected_scores(input: &str, args: &[String]) {
+    let mut game_name: &str = DEFAULT_GAME;
+    let mut strategies: Vec<GuideStrategy> = Vec::new();
+    let mut model_spec: Option<(&str, &str)> = None;
+    let mut n_samples: usize = DEFAULT_SAMPLES;
+    let mut seed: u64 = 0;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--game", Some(value)) => { game_name = value; Some(()) },
+            ("--strategy", Some(value)) => {
+                GuideStrategy::from_name(value).map(|x| strategies.push(x))
+            },
+            ("--distribution" | "--markov" | "--fit", Some(value)) => {
+                model_spec = Some((arg, value));
+                Some(())
+            },
+            ("--samples", Some(value)) => value.parse().ok()
+                .filter(|x| *x >= 2)
+                .map(|x| n_samples = x),
+            ("--seed", Some(value)) => value.parse().ok().map(|x| seed = x),
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            exit_with_error(ANALYZE_USAGE);
+        }
+    }
+
+    if strategies.is_empty() {
+        strategies = GuideStrategy::ALL.to_vec();
+    }
+
+    let game: Game = Game::load(game_name).unwrap_or_else(|error| exit_with_error(&error));
+    let model: OpponentModel = match model_spec {
+        Some(("--distribution", spec)) => OpponentModel::parse_distribution(&game, spec)
+            .map(OpponentModel::from_distribution),
+        Some(("--markov", path)) => fs::read_to_string(path)
+            .map_err(|error| format!("Unable to read {path}: {error}"))
+            .and_then(|table| OpponentModel::parse_markov(&game, &table)),
+        Some(("--fit", kind @ ("frequency" | "markov"))) => {
+            let history: Vec<usize> = input.lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .filter_map(|code| game.get_opponent_shape(code))
+                .map(|shape| shape.index)
+                .collect();
+            match kind {
+                "frequency" => Ok(OpponentModel::fit_frequency(&history, game.shapes.len())),
+                _ => Ok(OpponentModel::fit_markov(&history, game.shapes.len())),
+            }
+        },
+        _ => exit_with_error(ANALYZE_USAGE),
+    }.unwrap_or_else(|error| exit_with_error(&error));
+
+    for strategy in strategies {
+        // What each round of the guide scores against every possible opponent shape
+        let round_scores: Vec<Vec<f64>> = input.lines().enumerate().map(|(i, line)| {
+            let code: &str = line.split_whitespace().nth(1).unwrap_or("");
+            game.shapes.iter().map(|shape_opponent| {
+                match strategy.get_player_shape(&game, shape_opponent, code) {
+                    Some(shape_player) => game.get_score(shape_player, shape_opponent) as f64,
+                    None => exit_with_error(&format!("Invalid round at line {}: '{line}'", i + 1)),
+                }
+            }).collect()
+        }).collect();
+
+        let (mean, variance): (f64, f64) = analysis::compute_expected_score(&model, &round_scores);
+
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let samples: Vec<f64> =
+            analysis::simulate_scores(&model, &round_scores, n_samples, &mut rng);
+        let sample_mean: f64 = samples.iter().sum::<f64>() / n_samples as f64;
+        let sample_variance: f64 = samples.iter()
+            .map(|x| (x - sample_mean).powi(2))
+            .sum::<f64>() / (n_samples as f64 - 1.0);
+
+        println!("'{}' strategy:", strategy.name());
+        println!(
+            "  exact:       expected score {mean:.2}, variance {variance:.2}, sd {:.2}",
+            variance.sqrt()
+        );
+        println!(
+            "  monte carlo: mean {sample_mean:.2}, variance {sample_variance:.2}, sd {:.2} \
+            ({n_samples} samples)", sample_variance.sqrt()
+        );
+    }
 }
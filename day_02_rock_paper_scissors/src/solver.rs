@@ -0,0 +1,279 @@
+use std::ops::Range;
+
+use crate::game::{Game, Outcome};
+
+const NO_STATE: u32 = u32::MAX;
+
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Constraints {
+    // At most this many winning rounds
+    pub max_wins: Option<usize>,
+    // At most this many consecutive rounds playing the same shape
+    pub max_repeats: Option<usize>,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub score: usize,
+    pub wins: usize,
+    pub shapes: Vec<usize>,
+}
+
+
+// Scores of the states after a round, `None` for the unreachable ones
+type Layer = Vec<Option<usize>>;
+
+
+// Dynamic programming over (wins so far, last shape, length of its current run); the wins
+// and run dimensions collapse to a single value when their constraint can't bind. With
+// `outcomes_only`, only the shapes an outcome-based guide can express are considered.
+pub fn solve(
+    game: &Game, opponent: &[usize], constraints: Constraints, outcomes_only: bool
+) -> Option<Solution> {
+    let n_rounds: usize = opponent.len();
+    if n_rounds == 0 {
+        return Some(Solution { score: 0, wins: 0, shapes: Vec::new() });
+    }
+
+    let constraints: Constraints = Constraints {
+        max_wins: constraints.max_wins.filter(|x| *x < n_rounds),
+        max_repeats: constraints.max_repeats.filter(|x| *x < n_rounds),
+    };
+    let table: Table = Table::new(game, opponent, constraints, outcomes_only)?;
+
+    // Only some of the layers are kept on the way forward; the parents of the others are
+    // recomputed from them, one block of rounds at a time, on the way back
+    let block_size: usize = (2.0 * (n_rounds as f64).sqrt()).ceil().max(1.0) as usize;
+    let mut checkpoints: Vec<Option<Layer>> = Vec::new();
+    let mut best: Option<Layer> = None;
+    let mut parents: Vec<u32> = vec![NO_STATE; table.layer_size];
+
+    for i in 0..n_rounds {
+        if i % block_size == 0 {
+            checkpoints.push(best.clone());
+        }
+        best = Some(table.next_layer(i, best.as_deref(), &mut parents));
+    }
+
+    let (mut state, score): (usize, usize) = best.unwrap_or_default().iter().enumerate()
+        .filter_map(|(state, value)| value.map(|value| (state, value)))
+        .max_by_key(|(_, value)| *value)?;
+
+    let mut shapes: Vec<usize> = vec![0; n_rounds];
+    for (block, checkpoint) in checkpoints.into_iter().enumerate().rev() {
+        let rounds: Range<usize> = block * block_size..n_rounds.min((block + 1) * block_size);
+        let mut parents: Vec<u32> = vec![NO_STATE; rounds.len() * table.layer_size];
+
+        let mut best: Option<Layer> = checkpoint;
+        for (j, i) in rounds.clone().enumerate() {
+            let layer_parents: &mut [u32] = &mut parents[j * table.layer_size..];
+            best = Some(table.next_layer(i, best.as_deref(), layer_parents));
+        }
+
+        for (j, i) in rounds.enumerate().rev() {
+            shapes[i] = (state / table.n_runs) % table.n_shapes;
+            state = parents[j * table.layer_size + state] as usize;
+        }
+    }
+
+    let wins: usize = shapes.iter().zip(opponent)
+        .filter(|(s, o)| game.shapes[**s].get_outcome(&game.shapes[**o]) == Outcome::Victory)
+        .count();
+
+    Some(Solution { score, wins, shapes })
+}
+
+
+struct Table<'a> {
+    game: &'a Game,
+    opponent: &'a [usize],
+    constraints: Constraints,
+    outcomes_only: bool,
+    n_shapes: usize,
+    n_runs: usize,
+    n_wins: usize,
+    layer_size: usize,
+}
+
+impl<'a> Table<'a> {
+    fn new(
+        game: &'a Game, opponent: &'a [usize], constraints: Constraints, outcomes_only: bool
+    ) -> Option<Table<'a>> {
+        let n_shapes: usize = game.shapes.len();
+        let n_wins: usize = constraints.max_wins.map_or(1, |x| x + 1);
+        let n_runs: usize = match constraints.max_repeats {
+            Some(0) => return None,
+            Some(x) => x,
+            None => 1,
+        };
+        let layer_size: usize = n_wins * n_shapes * n_runs;
+
+        Some(Table {
+            game, opponent, constraints, outcomes_only, n_shapes, n_runs, n_wins, layer_size
+        })
+    }
+
+    // The layer after round `i`, from the one before it (`None` for the first round), writing
+    // the previous state of every reachable one into `parents`
+    fn next_layer(&self, i: usize, best: Option<&[Option<usize>]>, parents: &mut [u32]) -> Layer {
+        let (game, constraints) = (self.game, self.constraints);
+        let opponent_shape: usize = self.opponent[i];
+        let candidates: Vec<usize> = get_candidates(game, opponent_shape, self.outcomes_only);
+        let mut next: Layer = vec![None; self.layer_size];
+
+        // The first round has no previous state, so it comes from a virtual one
+        let previous_states: Vec<(usize, usize)> = match best {
+            None => vec![(usize::MAX, 0)],
+            Some(best) => best.iter().enumerate()
+                .filter_map(|(state, value)| value.map(|value| (state, value)))
+                .collect(),
+        };
+
+        for shape in candidates {
+            let score: usize = game.get_score(&game.shapes[shape], &game.shapes[opponent_shape]);
+            let win: usize = match game.shapes[shape].get_outcome(&game.shapes[opponent_shape]) {
+                Outcome::Victory if constraints.max_wins.is_some() => 1,
+                _ => 0,
+            };
+
+            for (previous, previous_score) in previous_states.iter().copied() {
+                let (w, r): (usize, usize) = match previous {
+                    usize::MAX => (win, 0),
+                    _ => {
+                        let previous_shape: usize = (previous / self.n_runs) % self.n_shapes;
+                        let previous_run: usize = previous % self.n_runs;
+                        let run: usize = match previous_shape == shape {
+                            true if constraints.max_repeats.is_some() => previous_run + 1,
+                            _ => 0,
+                        };
+                        (previous / self.n_runs / self.n_shapes + win, run)
+                    },
+                };
+                if w >= self.n_wins || r >= self.n_runs {
+                    continue;
+                }
+
+                let state: usize = (w * self.n_shapes + shape) * self.n_runs + r;
+                if next[state].is_none_or(|x| x < previous_score + score) {
+                    next[state] = Some(previous_score + score);
+                    parents[state] = previous as u32;
+                }
+            }
+        }
+
+        next
+    }
+}
+
+
+fn get_candidates(game: &Game, opponent_shape: usize, outcomes_only: bool) -> Vec<usize> {
+    if !outcomes_only {
+        return (0..game.shapes.len()).collect();
+    }
+
+    let mut candidates: Vec<usize> = [Outcome::Defeat, Outcome::Tie, Outcome::Victory].iter()
+        .map(|outcome| game.get_shape_for_outcome(&game.shapes[opponent_shape], *outcome).index)
+        .collect();
+    candidates.dedup();
+    candidates
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PRESETS;
+
+    // Best score over every guide that satisfies the constraints, trying every shape or every
+    // outcome in each round
+    fn brute_force(
+        game: &Game, opponent: &[usize], constraints: Constraints, outcomes_only: bool
+    ) -> Option<usize> {
+        let choices: Vec<Vec<usize>> = opponent.iter().map(|o| match outcomes_only {
+            false => (0..game.shapes.len()).collect(),
+            true => [Outcome::Defeat, Outcome::Tie, Outcome::Victory].iter()
+                .map(|x| game.get_shape_for_outcome(&game.shapes[*o], *x).index)
+                .collect(),
+        }).collect();
+        let n_guides: usize = choices.iter().map(|x| x.len()).product();
+
+        (0..n_guides).filter_map(|mut guide| {
+            let shapes: Vec<usize> = choices.iter().map(|x| {
+                let shape: usize = x[guide % x.len()];
+                guide /= x.len();
+                shape
+            }).collect();
+            check_guide(game, opponent, constraints, &shapes)
+        }).max()
+    }
+
+    // The score of the guide, if it satisfies the constraints
+    fn check_guide(
+        game: &Game, opponent: &[usize], constraints: Constraints, shapes: &[usize]
+    ) -> Option<usize> {
+        let wins: usize = shapes.iter().zip(opponent)
+            .filter(|(s, o)| game.shapes[**s].get_outcome(&game.shapes[**o]) == Outcome::Victory)
+            .count();
+        let longest_run: usize = shapes.chunk_by(|a, b| a == b).map(|x| x.len()).max()
+            .unwrap_or(0);
+
+        if constraints.max_wins.is_some_and(|x| wins > x)
+            || constraints.max_repeats.is_some_and(|x| longest_run > x) {
+            return None;
+        }
+
+        Some(shapes.iter().zip(opponent)
+            .map(|(s, o)| game.get_score(&game.shapes[*s], &game.shapes[*o]))
+            .sum())
+    }
+
+    #[test]
+    fn empty_input_has_empty_guide() {
+        let game: Game = Game::load("rps").unwrap();
+        let constraints: Constraints = Constraints { max_wins: Some(0), max_repeats: Some(0) };
+        let solution: Solution = solve(&game, &[], constraints, false).unwrap();
+
+        assert_eq!((solution.score, solution.wins, solution.shapes.len()), (0, 0, 0));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut seed: u64 = 2022;
+        let mut random = |bound: usize| -> usize {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+
+        for (name, table) in PRESETS.iter().take(2) {
+            let game: Game = Game::from_table(table).unwrap();
+            let max_rounds: usize = if game.shapes.len() > 3 { 5 } else { 7 };
+
+            for _ in 0..150 {
+                let opponent: Vec<usize> =
+                    (0..random(max_rounds + 1)).map(|_| random(game.shapes.len())).collect();
+                let constraints: Constraints = Constraints {
+                    max_wins: [None, Some(random(opponent.len() + 2))][random(2)],
+                    max_repeats: [None, Some(random(opponent.len() + 2))][random(2)],
+                };
+
+                for outcomes_only in [false, true] {
+                    let expected: Option<usize> =
+                        brute_force(&game, &opponent, constraints, outcomes_only);
+                    let solution: Option<Solution> =
+                        solve(&game, &opponent, constraints, outcomes_only);
+                    let context: String = format!("{name} {opponent:?} {constraints:?}");
+
+                    assert_eq!(solution.as_ref().map(|x| x.score), expected, "{context}");
+                    if let Some(solution) = solution {
+                        assert_eq!(
+                            check_guide(&game, &opponent, constraints, &solution.shapes),
+                            Some(solution.score), "{context}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::game::{Game, Outcome};
+
+pub const STRATEGY_NAMES: [&str; 6] = [
+    "guide", "input", "random", "frequency", "markov", "beat-last",
+];
+
+
+// Strategies work with indexes of `Game::shapes`, so they fit any cyclic game
+pub trait Strategy {
+    fn play(&mut self, game: &Game, own_history: &[usize], opponent_history: &[usize]) -> usize;
+}
+
+
+// Plays a fixed sequence of shapes, starting over when it runs out
+pub struct FixedGuide {
+    shapes: Vec<usize>,
+}
+
+impl Strategy for FixedGuide {
+    fn play(&mut self, _: &Game, own_history: &[usize], _: &[usize]) -> usize {
+        self.shapes[own_history.len() % self.shapes.len()]
+    }
+}
+
+
+pub struct RandomChoice {
+    rng: StdRng,
+}
+
+impl Strategy for RandomChoice {
+    fn play(&mut self, game: &Game, _: &[usize], _: &[usize]) -> usize {
+        self.rng.gen_range(0..game.shapes.len())
+    }
+}
+
+
+// Beats the shape the opponent has played the most so far
+pub struct FrequencyCounter {
+    rng: StdRng,
+    counts: Vec<usize>,
+}
+
+impl Strategy for FrequencyCounter {
+    fn play(&mut self, game: &Game, _: &[usize], opponent_history: &[usize]) -> usize {
+        // Only the last move is new since the previous round
+        self.counts.resize(game.shapes.len(), 0);
+        if let Some(last) = opponent_history.last() {
+            self.counts[*last] += 1;
+        }
+
+        match get_most_frequent(&self.counts) {
+            Some(predicted) => get_winning_shape(game, predicted),
+            None => self.rng.gen_range(0..game.shapes.len()),
+        }
+    }
+}
+
+
+// Predicts the opponent's next shape from what followed its last shape before
+pub struct MarkovPredictor {
+    rng: StdRng,
+    transitions: Vec<Vec<usize>>,
+}
+
+impl Strategy for MarkovPredictor {
+    fn play(&mut self, game: &Game, _: &[usize], opponent_history: &[usize]) -> usize {
+        let n_shapes: usize = game.shapes.len();
+        self.transitions.resize(n_shapes, vec![0; n_shapes]);
+        if let [.., previous, last] = opponent_history {
+            self.transitions[*previous][*last] += 1;
+        }
+
+        let predicted: Option<usize> = opponent_history.last()
+            .and_then(|last| get_most_frequent(&self.transitions[*last]));
+
+        match predicted {
+            Some(predicted) => get_winning_shape(game, predicted),
+            None => self.rng.gen_range(0..game.shapes.len()),
+        }
+    }
+}
+
+
+pub struct BeatLastMove {
+    rng: StdRng,
+}
+
+impl Strategy for BeatLastMove {
+    fn play(&mut self, game: &Game, _: &[usize], opponent_history: &[usize]) -> usize {
+        match opponent_history.last() {
+            Some(last) => get_winning_shape(game, *last),
+            None => self.rng.gen_range(0..game.shapes.len()),
+        }
+    }
+}
+
+
+fn get_most_frequent(counts: &[usize]) -> Option<usize> {
+    // Ties go to the first shape of the game
+    let max: usize = *counts.iter().max()?;
+    if max == 0 {
+        return None;
+    }
+
+    counts.iter().position(|count| *count == max)
+}
+
+
+fn get_winning_shape(game: &Game, shape: usize) -> usize {
+    game.get_shape_for_outcome(&game.shapes[shape], Outcome::Victory).index
+}
+
+
+// The "guide" and "input" strategies replay the player and opponent columns of the input
+pub fn create_strategy(
+    name: &str, game: &Game, input: &str, seed: u64
+) -> Result<Box<dyn Strategy>, String> {
+    let rng: StdRng = StdRng::seed_from_u64(seed);
+
+    match name {
+        "guide" | "input" => {
+            let column: usize = if name == "guide" { 1 } else { 0 };
+            let shapes: Vec<usize> = input.lines().enumerate().map(|(i, line)| {
+                let code: &str = line.split_whitespace().nth(column).unwrap_or("");
+                let shape = match column {
+                    0 => game.get_opponent_shape(code),
+                    _ => game.get_player_shape(code),
+                };
+                shape.map(|shape| shape.index)
+                    .ok_or_else(|| format!("Invalid round at line {}: '{line}'", i + 1))
+            }).collect::<Result<Vec<usize>, String>>()?;
+
+            if shapes.is_empty() {
+                return Err(String::from("The input has no rounds to replay"));
+            }
+            Ok(Box::new(FixedGuide { shapes }))
+        },
+        "random" => Ok(Box::new(RandomChoice { rng })),
+        "frequency" => Ok(Box::new(FrequencyCounter { rng, counts: Vec::new() })),
+        "markov" => Ok(Box::new(MarkovPredictor { rng, transitions: Vec::new() })),
+        "beat-last" => Ok(Box::new(BeatLastMove { rng })),
+        _ => Err(format!(
+            "Unknown strategy '{name}', expected one of: {}", STRATEGY_NAMES.join(", ")
+        )),
+    }
+}
+
+
+pub fn play_match(
+    game: &Game, player_a: &mut dyn Strategy, player_b: &mut dyn Strategy, n_rounds: usize
+) -> (usize, usize) {
+    let mut history_a: Vec<usize> = Vec::with_capacity(n_rounds);
+    let mut history_b: Vec<usize> = Vec::with_capacity(n_rounds);
+    let (mut score_a, mut score_b): (usize, usize) = (0, 0);
+
+    for _ in 0..n_rounds {
+        let shape_a: usize = player_a.play(game, &history_a, &history_b);
+        let shape_b: usize = player_b.play(game, &history_b, &history_a);
+
+        score_a += game.get_score(&game.shapes[shape_a], &game.shapes[shape_b]);
+        score_b += game.get_score(&game.shapes[shape_b], &game.shapes[shape_a]);
+
+        history_a.push(shape_a);
+        history_b.push(shape_b);
+    }
+
+    (score_a, score_b)
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ScoreDistribution {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: usize,
+    pub median: usize,
+    pub max: usize,
+}
+
+impl ScoreDistribution {
+    pub fn from_scores(scores: &[usize]) -> ScoreDistribution {
+        let mut sorted: Vec<usize> = scores.to_vec();
+        sorted.sort_unstable();
+
+        let n: f64 = sorted.len() as f64;
+        let mean: f64 = sorted.iter().sum::<usize>() as f64 / n;
+        let variance: f64 = sorted.iter().map(|x| (*x as f64 - mean).powi(2)).sum::<f64>() / n;
+
+        ScoreDistribution {
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            median: sorted[sorted.len() / 2],
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use aoc_common::exit_with_error;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::priorities::PriorityTable;
+use crate::{get_badges_priorities_sum, get_group_badges, get_priorities_sum, GroupBadge};
+use crate::{split_string_in_half, DEFAULT_GROUP_SIZE};
+
+const DEFAULT_GROUPS: usize = 100_000;
+const N_OF_RUNS: usize = 5;
+const SEED: u64 = 2022;
+
+const LOWERCASE_OFFSET: u32 = 'a' as u32;
+const UPPERCASE_OFFSET: u32 = 'A' as u32;
+const N_OF_LETTERS: u32 = 26;
+
+type SumFunction<'a> = &'a dyn Fn(&str) -> u64;
+
+
+pub fn run_benchmark(args: &[String]) {
+    let n_groups: usize = match args.first().map(|x| x.parse::<usize>()) {
+        Some(Ok(n_groups)) => n_groups,
+        None => DEFAULT_GROUPS,
+        Some(Err(_)) => exit_with_error("Usage: bench [number of groups]"),
+    };
+
+    let input: String = generate_input(n_groups, &mut StdRng::seed_from_u64(SEED));
+    println!("Generated {} rucksacks ({} bytes)", 3 * n_groups, input.len());
+
+    let table: PriorityTable = PriorityTable::letters();
+    let benchmarks: [(&str, SumFunction, SumFunction); 2] = [
+        ("priorities", &get_priorities_sum_hashset, &|input| {
+            get_priorities_sum(input, &table).unwrap()
+        }),
+        ("badges", &get_badges_priorities_sum_hashset, &|input| {
+            let badges: Vec<GroupBadge> =
+                get_group_badges(input, DEFAULT_GROUP_SIZE, &table).unwrap();
+            get_badges_priorities_sum(&badges, &table)
+        }),
+    ];
+
+    for (name, hashset_version, bitset_version) in benchmarks {
+        let (hashset_result, hashset_time): (u64, Duration) = time(hashset_version, &input);
+        let (bitset_result, bitset_time): (u64, Duration) = time(bitset_version, &input);
+        assert_eq!(hashset_result, bitset_result, "The {name} sums don't match");
+
+        println!(
+            "{name:<10} HashSet {:>9.2} ms | ItemSet {:>9.2} ms | {:.1}x faster",
+            hashset_time.as_secs_f64() * 1000.0, bitset_time.as_secs_f64() * 1000.0,
+            hashset_time.as_secs_f64() / bitset_time.as_secs_f64()
+        );
+    }
+}
+
+
+fn time(function: SumFunction, input: &str) -> (u64, Duration) {
+    // Best of a few runs, to reduce the noise
+    (0..N_OF_RUNS).map(|_| {
+        let start: Instant = Instant::now();
+        let result: u64 = function(input);
+        (result, start.elapsed())
+    }).min_by_key(|(_, elapsed)| *elapsed).unwrap()
+}
+
+
+// Each group gets a badge and splits the other items between its three elves, so that
+// every compartment pair and every group has exactly one item in common
+fn generate_input(n_groups: usize, rng: &mut StdRng) -> String {
+    let items: Vec<char> = (0..N_OF_LETTERS)
+        .flat_map(|i| [LOWERCASE_OFFSET + i, UPPERCASE_OFFSET + i])
+        .filter_map(char::from_u32)
+        .collect();
+    let mut input: String = String::new();
+
+    for _ in 0..n_groups {
+        let mut shuffled: Vec<char> = items.clone();
+        shuffled.shuffle(rng);
+        let (badge, others): (char, &[char]) = (shuffled[0], &shuffled[1..]);
+
+        for pool in others.chunks(others.len() / 3) {
+            let (shared, rest): (char, &[char]) = (pool[0], &pool[1..]);
+            let (side_a, side_b): (&[char], &[char]) = rest.split_at(rest.len() / 2);
+            let length: usize = rng.gen_range(2..=2 * side_a.len());
+
+            let mut compartment_a: Vec<char> = vec![shared, badge];
+            compartment_a.extend((2..length).map(|_| *side_a.choose(rng).unwrap()));
+            let mut compartment_b: Vec<char> = vec![shared];
+            compartment_b.extend((1..length).map(|_| *side_b.choose(rng).unwrap()));
+
+            compartment_a.shuffle(rng);
+            compartment_b.shuffle(rng);
+            input.extend(compartment_a.iter().chain(compartment_b.iter()));
+            input.push('\n');
+        }
+    }
+
+    input
+}
+
+
+// The original versions of both parts, kept as a baseline
+fn get_priorities_sum_hashset(input_str: &str) -> u64 {
+    let mut priorities_sum: u64 = 0;
+
+    for rusack in input_str.lines() {
+        let (compartment_a, compartment_b): (&str, &str) = split_string_in_half(rusack);
+
+        let set_a: HashSet<char> = create_hash_set_of_chars(compartment_a);
+        let set_b: HashSet<char> = create_hash_set_of_chars(compartment_b);
+
+        let shared_item: char = get_element_in_common(&set_a, &set_b);
+        priorities_sum += get_priority_value(shared_item);
+    }
+
+    priorities_sum
+}
+
+
+fn get_badges_priorities_sum_hashset(input_str: &str) -> u64 {
+    let mut badges_priorities_sum: u64 = 0;
+
+    for rusack_chunk in input_str.lines().collect::<Vec<&str>>().chunks(3) {
+        // VERY messy, but better than instantiating every hash map individually
+        let intersection_set: HashSet<char> = rusack_chunk.iter().fold(
+            HashSet::new(), |set, x| {
+                if set.is_empty() {
+                    create_hash_set_of_chars(x)
+                } else {
+                    get_intersection(&set, &create_hash_set_of_chars(x))
+                }
+            }
+        );
+
+        let shared_item: char = intersection_set.iter().copied().next().expect("Error!");
+        badges_priorities_sum += get_priority_value(shared_item);
+    }
+
+    badges_priorities_sum
+}
+
+
+fn create_hash_set_of_chars(string: &str) -> HashSet<char> {
+    HashSet::from_iter(string.chars())
+}
+
+
+fn get_element_in_common(set_a: &HashSet<char>, set_b: &HashSet<char>) -> char {
+    set_a.intersection(set_b).copied().next().expect("Error!")
+}
+
+
+fn get_intersection(set_a: &HashSet<char>, set_b: &HashSet<char>) -> HashSet<char> {
+    set_a.intersection(set_b).copied().collect()
+}
+
+
+fn get_priority_value(item: char) -> u64 {
+    let item_ascii: u32 = item as u32;
+
+    // We need to do this check because the ASCII values of uppercase letters is
+    // actually lesser than the values of lowercases
+    if item_ascii >= LOWERCASE_OFFSET {
+        return (item_ascii - LOWERCASE_OFFSET + 1).into()
+    }
+
+    (item_ascii - UPPERCASE_OFFSET + N_OF_LETTERS + 1).into()
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::item_set::{self, ItemSet};
+use crate::priorities::PriorityTable;
+
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub lines: Range<usize>,  // 1-based, end exclusive
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.lines.len() {
+            1 => write!(f, "line {}: {}", self.lines.start, self.message),
+            _ => write!(f, "lines {}-{}: {}", self.lines.start, self.lines.end - 1, self.message),
+        }
+    }
+}
+
+
+pub fn diagnose(input_str: &str, group_size: usize, table: &PriorityTable) -> Vec<Finding> {
+    let rusacks: Vec<&str> = input_str.lines().collect();
+    let mut findings: Vec<Finding> = Vec::new();
+
+    for (i, rusack) in rusacks.iter().enumerate() {
+        let line: usize = i + 1;
+        findings.extend(diagnose_rusack(rusack, table).into_iter().map(|message| Finding {
+            lines: line..line + 1,
+            message,
+        }));
+    }
+
+    for (group, rusack_chunk) in rusacks.chunks(group_size).enumerate() {
+        let first_line: usize = group * group_size + 1;
+        let lines: Range<usize> = first_line..first_line + rusack_chunk.len();
+
+        if rusack_chunk.len() < group_size {
+            findings.push(Finding {
+                lines: lines.clone(),
+                message: format!(
+                    "incomplete group with {} of {group_size} rucksacks", rusack_chunk.len()
+                ),
+            });
+        }
+
+        // Groups with unknown items were already reported above; when no badge is shared,
+        // the items held between them show how far apart the rucksacks are
+        if let Ok(badges) = item_set::intersect_all(rusack_chunk.iter().copied(), table) {
+            let message: Option<String> = match badges.len() {
+                0 => {
+                    let held: ItemSet = rusack_chunk.iter()
+                        .filter_map(|rusack| ItemSet::from_items(rusack, table).ok())
+                        .fold(ItemSet::new(), |held, x| held.union(&x));
+                    Some(format!("group shares no items, {} held between them", held.len()))
+                },
+                1 => None,
+                _ => Some(format!("group shares {}", describe_items(&badges, table))),
+            };
+            findings.extend(message.map(|message| Finding { lines, message }));
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.lines.start);
+    findings
+}
+
+
+fn diagnose_rusack(rusack: &str, table: &PriorityTable) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    let n_items: usize = rusack.chars().count();
+
+    if n_items == 0 {
+        return vec![String::from("empty rucksack")];
+    }
+
+    if n_items % 2 == 1 {
+        messages.push(format!(
+            "odd number of items ({n_items}), compartments would have {} and {}",
+            n_items / 2, n_items - n_items / 2
+        ));
+    }
+
+    let unknown: Vec<String> = rusack.chars().enumerate()
+        .filter(|(_, c)| table.get_index(*c).is_none())
+        .map(|(column, c)| format!("{c:?} at column {}", column + 1))
+        .collect();
+    if !unknown.is_empty() {
+        messages.push(format!("items without a priority: {}", unknown.join(", ")));
+    }
+
+    // Compartments can only be compared when the items and the split make sense
+    let (compartment_a, compartment_b): (&str, &str) = crate::split_string_in_half(rusack);
+    let compartments = (
+        ItemSet::from_items(compartment_a, table), ItemSet::from_items(compartment_b, table)
+    );
+    if let (true, (Ok(set_a), Ok(set_b))) = (messages.is_empty(), compartments) {
+        let shared: ItemSet = set_a.intersection(&set_b);
+        if shared.len() != 1 {
+            messages.push(format!("compartments share {}", describe_items(&shared, table)));
+        }
+    }
+
+    messages
+}
+
+
+fn describe_items(items: &ItemSet, table: &PriorityTable) -> String {
+    match items.len() {
+        0 => String::from("no items"),
+        n => {
+            let names: Vec<String> = items.iter()
+                .map(|x| String::from(table.get_symbol(x)))
+                .collect();
+            format!("{n} items: {}", names.join(", "))
+        },
+    }
+}
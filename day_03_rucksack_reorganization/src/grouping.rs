@@ -0,0 +1,232 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use aoc_common::LineError;
+
+use crate::item_set::ItemSet;
+use crate::priorities::PriorityTable;
+use crate::{parse_rusack, DEFAULT_GROUP_SIZE};
+
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub rusacks: Vec<usize>,  // Indexes of the rucksacks, in increasing order
+    pub badge: usize,  // Index of the item in the priority table
+}
+
+
+// Exact cover without listing the groups up front: each step builds, for every unassigned
+// rucksack, the groups of unassigned rucksacks it can still be part of, stopping as soon as
+// there are as many as the fewest found so far, and tries each group of the rucksack with the
+// fewest. A rucksack left without groups ends the branch right away.
+struct Search<'a> {
+    rusacks: &'a [ItemSet],
+    group_size: usize,
+    partners: Vec<Vec<usize>>,  // Rucksacks sharing some item with each one, in increasing order
+    assigned: Vec<bool>,
+    chosen: Vec<Group>,
+}
+
+impl Search<'_> {
+    // Up to `limit` groups that include `rusack`; the other members are added in increasing
+    // order and a branch is dropped once its members share nothing
+    fn get_groups(&self, rusack: usize, limit: usize) -> Vec<Group> {
+        let partners: Vec<usize> = self.partners[rusack].iter().copied()
+            .filter(|x| !self.assigned[*x])
+            .collect();
+        let mut groups: Vec<Group> = Vec::new();
+        self.extend_groups(&partners, &mut vec![rusack], self.rusacks[rusack], limit, &mut groups);
+        groups
+    }
+
+    fn extend_groups(
+        &self, partners: &[usize], members: &mut Vec<usize>, shared: ItemSet, limit: usize,
+        groups: &mut Vec<Group>
+    ) {
+        if members.len() == self.group_size {
+            if shared.len() == 1 {
+                let mut rusacks: Vec<usize> = members.clone();
+                rusacks.sort_unstable();
+                groups.push(Group { rusacks, badge: shared.iter().next().unwrap() });
+            }
+            return;
+        }
+
+        for (i, partner) in partners.iter().copied().enumerate() {
+            let items: ItemSet = shared.intersection(&self.rusacks[partner]);
+            if items.is_empty() {
+                continue;
+            }
+
+            members.push(partner);
+            self.extend_groups(&partners[i + 1..], members, items, limit, groups);
+            members.pop();
+
+            if groups.len() >= limit {
+                return;
+            }
+        }
+    }
+
+    fn solve(&mut self) -> bool {
+        let mut fewest: Option<Vec<Group>> = None;
+        for rusack in (0..self.assigned.len()).filter(|x| !self.assigned[*x]) {
+            let limit: usize = fewest.as_ref().map_or(usize::MAX, |x| x.len());
+            let groups: Vec<Group> = self.get_groups(rusack, limit);
+            match groups.len() {
+                0 => return false,
+                n if n < limit => fewest = Some(groups),
+                _ => {},
+            }
+        }
+
+        let Some(options) = fewest else {
+            return true;
+        };
+
+        for group in options {
+            group.rusacks.iter().for_each(|x| self.assigned[*x] = true);
+            self.chosen.push(group);
+            if self.solve() {
+                return true;
+            }
+
+            let group: Group = self.chosen.pop().unwrap();
+            group.rusacks.iter().for_each(|x| self.assigned[*x] = false);
+        }
+
+        false
+    }
+}
+
+
+// Partition of the rucksacks into groups whose members share exactly one item, if any exists
+pub fn find_groups(rusacks: &[ItemSet], group_size: usize) -> Option<Vec<Group>> {
+    if !rusacks.len().is_multiple_of(group_size) {
+        return None;
+    }
+
+    let partners: Vec<Vec<usize>> = rusacks.iter().enumerate().map(|(i, items)| {
+        (0..rusacks.len())
+            .filter(|j| *j != i && !items.intersection(&rusacks[*j]).is_empty())
+            .collect()
+    }).collect();
+
+    let mut search: Search = Search {
+        rusacks,
+        group_size,
+        partners,
+        assigned: vec![false; rusacks.len()],
+        chosen: Vec::new(),
+    };
+
+    match search.solve() {
+        true => Some(search.chosen),
+        false => None,
+    }
+}
+
+
+// Forgets the order of the input (shuffling it when a seed is given) and groups it again.
+// Only triples are supported here: a step can try every pair of partners of a rucksack, and
+// with bigger groups the number of options grows with a higher power of the partner count.
+pub fn print_groups(
+    input_str: &str, table: &PriorityTable, seed: Option<u64>
+) -> Result<(), LineError> {
+    let group_size: usize = DEFAULT_GROUP_SIZE;
+    let lines: Vec<&str> = input_str.lines().collect();
+    let mut order: Vec<usize> = (0..lines.len()).collect();
+    if let Some(seed) = seed {
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+
+    let rusacks: Vec<ItemSet> = order.iter()
+        .map(|i| parse_rusack(lines[*i], i + 1, table))
+        .collect::<Result<Vec<ItemSet>, LineError>>()?;
+    let Some(mut groups) = find_groups(&rusacks, group_size) else {
+        println!(
+            "The {} rucksacks can't be split into groups of {group_size} with one badge each.",
+            lines.len()
+        );
+        return Ok(());
+    };
+
+    // Back to the line numbers of the input
+    for group in groups.iter_mut() {
+        group.rusacks = group.rusacks.iter().map(|i| order[*i] + 1).collect();
+        group.rusacks.sort_unstable();
+    }
+    groups.sort_by_key(|group| group.rusacks[0]);
+
+    for group in groups.iter() {
+        let lines: Vec<String> = group.rusacks.iter().map(|x| x.to_string()).collect();
+        println!("lines {}: badge {}", lines.join(", "), table.get_symbol(group.badge));
+    }
+
+    let badges_priorities_sum: u64 = groups.iter().map(|x| table.get_priority(x.badge)).sum();
+    println!(
+        "Found {} groups, the sum of priorities for the badges is {badges_priorities_sum}.",
+        groups.len()
+    );
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(rusacks: &[&str]) -> Vec<ItemSet> {
+        let table: PriorityTable = PriorityTable::letters();
+        rusacks.iter().map(|x| ItemSet::from_items(x, &table).unwrap()).collect()
+    }
+
+    // Checks that the groups split all the rucksacks and that each one shares only its badge
+    fn check_groups(rusacks: &[ItemSet], groups: &[Group]) {
+        let mut members: Vec<usize> = groups.iter().flat_map(|x| x.rusacks.clone()).collect();
+        members.sort_unstable();
+        assert_eq!(members, (0..rusacks.len()).collect::<Vec<usize>>());
+
+        for group in groups {
+            let shared: ItemSet = group.rusacks.iter().map(|x| rusacks[*x]).collect();
+            assert_eq!(shared.iter().collect::<Vec<usize>>(), vec![group.badge]);
+        }
+    }
+
+    #[test]
+    fn finds_the_only_partition() {
+        let rusacks: Vec<ItemSet> = parse(&["ab", "xy", "ac", "xz", "ad", "xw"]);
+        let mut groups: Vec<Group> = find_groups(&rusacks, 3).unwrap();
+        groups.sort_by_key(|group| group.rusacks[0]);
+
+        let found: Vec<(Vec<usize>, char)> = groups.into_iter()
+            .map(|group| (group.rusacks, PriorityTable::letters().get_symbol(group.badge)))
+            .collect();
+        assert_eq!(found, vec![(vec![0, 2, 4], 'a'), (vec![1, 3, 5], 'x')]);
+    }
+
+    #[test]
+    fn finds_groups_for_the_shuffled_example() {
+        // The example of the puzzle, with the two groups interleaved
+        let rusacks: Vec<ItemSet> = parse(&[
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "ttgJtRGJQctTZtZT",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+            "PmmdzqPrVvPwwTWBwg",
+        ]);
+        check_groups(&rusacks, &find_groups(&rusacks, 3).unwrap());
+    }
+
+    #[test]
+    fn finds_no_groups_when_no_partition_exists() {
+        // Nothing in common, two items in common, and a rucksack that fits no group
+        assert!(find_groups(&parse(&["ab", "bc", "cd"]), 3).is_none());
+        assert!(find_groups(&parse(&["ab", "ab", "ab"]), 3).is_none());
+        assert!(find_groups(&parse(&["ax", "ay", "az", "bx", "by", "cz"]), 3).is_none());
+        assert!(find_groups(&parse(&["ab", "ab"]), 3).is_none());
+    }
+}
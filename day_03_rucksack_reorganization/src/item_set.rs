@@ -0,0 +1,93 @@
+use crate::priorities::{PriorityTable, UnknownItem};
+
+const N_OF_WORDS: usize = 4;
+pub const MAX_ITEMS: usize = 64 * N_OF_WORDS;
+
+
+// Set of items stored as a bitmask, where bit `n` is the `n`-th symbol of a priority table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ItemSet([u64; N_OF_WORDS]);
+
+impl ItemSet {
+    pub fn new() -> ItemSet {
+        ItemSet([0; N_OF_WORDS])
+    }
+
+    // Every item of any table, i.e. the identity of the intersection
+    pub fn all() -> ItemSet {
+        ItemSet([u64::MAX; N_OF_WORDS])
+    }
+
+    pub fn from_items(items: &str, table: &PriorityTable) -> Result<ItemSet, UnknownItem> {
+        let mut set: ItemSet = ItemSet::new();
+        for (column, item) in items.chars().enumerate() {
+            let index: usize = table.get_index(item)
+                .ok_or(UnknownItem { item, column: column + 1 })?;
+            set.insert(index);
+        }
+
+        Ok(set)
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(std::array::from_fn(|i| self.0[i] & other.0[i]))
+    }
+
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(std::array::from_fn(|i| self.0[i] | other.0[i]))
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    pub fn iter(&self) -> ItemSetIter {
+        ItemSetIter { words: self.0, word: 0 }
+    }
+}
+
+
+impl FromIterator<ItemSet> for ItemSet {
+    // Intersection of every set, so an empty iterator gives every item
+    fn from_iter<I: IntoIterator<Item = ItemSet>>(sets: I) -> ItemSet {
+        sets.into_iter().fold(ItemSet::all(), |set, x| set.intersection(&x))
+    }
+}
+
+
+// Items shared by all the rucksacks, however many there are
+pub fn intersect_all<'a>(
+    rucksacks: impl IntoIterator<Item = &'a str>, table: &PriorityTable
+) -> Result<ItemSet, UnknownItem> {
+    rucksacks.into_iter().map(|rucksack| ItemSet::from_items(rucksack, table)).collect()
+}
+
+
+// Yields the indexes of the items in the table, in increasing order
+pub struct ItemSetIter {
+    words: [u64; N_OF_WORDS],
+    word: usize,
+}
+
+impl Iterator for ItemSetIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.words.get(self.word)? == &0 {
+            self.word += 1;
+        }
+
+        let bits: &mut u64 = &mut self.words[self.word];
+        let index: usize = 64 * self.word + bits.trailing_zeros() as usize;
+        *bits &= *bits - 1;
+        Some(index)
+    }
+}
@@ -1,21 +1,95 @@
+use std::env;
 use std::fs;
-use std::collections::HashSet;
+use std::ops::Range;
+
+mod bench;
+mod diagnostics;
+mod grouping;
+mod item_set;
+mod priorities;
+
+use aoc_common::{exit_with_error, LineError};
+
+use item_set::ItemSet;
+use priorities::{PriorityTable, UnknownItem, DEFAULT_PRIORITIES};
 
 const INPUT_FILE_PATH: &str = "input";
+const DEFAULT_GROUP_SIZE: usize = 3;
 
-const LOWERCASE_OFFSET: u32 = 'a' as u32;
-const UPPERCASE_OFFSET: u32 = 'A' as u32;
-const N_OF_LETTERS: u32 = 26;
+const USAGE: &str = "Usage: [diagnose] [--group-size <n>] [--priorities <preset or file>] \
+    | regroup [--seed <seed>] [--priorities <preset or file>] | bench [number of groups]";
 
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(|x| x.as_str()) == Some("bench") {
+        return bench::run_benchmark(&args[1..]);
+    }
+
+    let mode: Option<&str> = args.first().map(|x| x.as_str())
+        .filter(|x| ["diagnose", "regroup"].contains(x));
+    let options: &[String] = if mode.is_some() { &args[1..] } else { &args };
+
+    let mut group_size: usize = DEFAULT_GROUP_SIZE;
+    let mut seed: Option<u64> = None;
+    let mut priorities_name: &str = DEFAULT_PRIORITIES;
+
+    let mut args_iter = options.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--group-size", Some(x)) if mode != Some("regroup") => {
+                x.parse::<usize>().ok().filter(|x| *x > 0).map(|x| group_size = x)
+            },
+            ("--seed", Some(x)) if mode == Some("regroup") => {
+                x.parse::<u64>().ok().map(|x| seed = Some(x))
+            },
+            ("--priorities", Some(x)) => { priorities_name = x; Some(()) },
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            exit_with_error(USAGE);
+        }
+    }
+
+    let table: PriorityTable = PriorityTable::load(priorities_name)
+        .unwrap_or_else(|error| exit_with_error(&error));
     let input: String = read_input_file(INPUT_FILE_PATH);
 
-    let priorities_sum: u64 = get_priorities_sum(&input);
-    let badges_priorities_sum: u64 = get_badges_priorities_sum(&input);
+    match mode {
+        Some("diagnose") => {
+            let findings: Vec<diagnostics::Finding> =
+                diagnostics::diagnose(&input, group_size, &table);
+            findings.iter().for_each(|finding| println!("{finding}"));
+            return println!("{} anomalies found.", findings.len());
+        },
+        Some("regroup") => {
+            return grouping::print_groups(&input, &table, seed)
+                .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+        },
+        _ => {},
+    }
+
+    let priorities_sum: u64 = get_priorities_sum(&input, &table)
+        .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+    let badges: Vec<GroupBadge> = get_group_badges(&input, group_size, &table)
+        .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+    let badges_priorities_sum: u64 = get_badges_priorities_sum(&badges, &table);
 
     println!("The sum of priorities is {priorities_sum}.");
     println!("The sum of priorities for the badges is {badges_priorities_sum}.");
+
+    for badge in badges.iter().filter(|badge| badge.items.len() != 1) {
+        let items: Vec<String> = badge.items.iter()
+            .map(|x| String::from(table.get_symbol(x)))
+            .collect();
+        println!(
+            "Warning: group {} (lines {}-{}) shares {} items instead of one{}",
+            badge.group, badge.lines.start, badge.lines.end - 1, badge.items.len(),
+            if badge.items.is_empty() { String::new() } else { format!(": {}", items.join(", ")) }
+        );
+    }
 }
 
 
@@ -28,74 +102,84 @@ fn read_input_file(file_path: &str) -> String {
 }
 
 
-fn get_priorities_sum(input_str: &str) -> u64 {
-    let mut priorities_sum: u64 = 0;
-
-    for rusack in input_str.lines() {
-        let (compartment_a, compartment_b): (&str, &str) = split_string_in_half(rusack);
 
-        let set_a: HashSet<char> = create_hash_set_of_chars(compartment_a);
-        let set_b: HashSet<char> = create_hash_set_of_chars(compartment_b);
+fn get_line_error(line: usize, content: &str, reason: &str) -> LineError {
+    LineError { line, content: String::from(content), reason: String::from(reason) }
+}
 
-        let shared_item: char = get_element_in_common(&set_a, &set_b);
-        priorities_sum += get_priority_value(shared_item);
-    }
 
-    priorities_sum
+// `line` is 1-based
+fn parse_rusack(rusack: &str, line: usize, table: &PriorityTable) -> Result<ItemSet, LineError> {
+    ItemSet::from_items(rusack, table)
+        .map_err(|error| get_line_error(line, rusack, &error.to_string()))
 }
 
 
-fn get_badges_priorities_sum(input_str: &str) -> u64 {
-    let mut badges_priorities_sum: u64 = 0;
+fn get_priorities_sum(input_str: &str, table: &PriorityTable) -> Result<u64, LineError> {
+    let mut priorities_sum: u64 = 0;
 
-    for rusack_chunk in input_str.lines().collect::<Vec<&str>>().chunks(3) {
-        // VERY messy, but better than instantiating every hash map individually
-        let intersection_set: HashSet<char> = rusack_chunk.iter().fold(
-            HashSet::new(), |set, x| {
-                if set.is_empty() {
-                    create_hash_set_of_chars(x)
-                } else {
-                    get_intersection(&set, &create_hash_set_of_chars(x))
-                }
-            }
-        );
+    for (i, rusack) in input_str.lines().enumerate() {
+        let (compartment_a, compartment_b): (&str, &str) = split_string_in_half(rusack);
 
-        let shared_item: char = intersection_set.iter().map(|x| *x).next().expect("Error!");
-        badges_priorities_sum += get_priority_value(shared_item);
+        let error = |error: UnknownItem| get_line_error(i + 1, rusack, &error.to_string());
+        let set_a: ItemSet = ItemSet::from_items(compartment_a, table).map_err(error)?;
+        // Columns of the second compartment are counted from the start of the rucksack
+        let offset: usize = compartment_a.chars().count();
+        let set_b: ItemSet = ItemSet::from_items(compartment_b, table)
+            .map_err(|x| error(UnknownItem { column: x.column + offset, ..x }))?;
+
+        let shared_item: usize = set_a.intersection(&set_b).iter().next()
+            .ok_or_else(|| get_line_error(i + 1, rusack, "the compartments share no items"))?;
+        priorities_sum += table.get_priority(shared_item);
     }
 
-    badges_priorities_sum
+    Ok(priorities_sum)
 }
 
 
-fn split_string_in_half(string: &str) -> (&str, &str) {
-    (&string[..string.len() / 2], &string[string.len() / 2..])
+#[derive(Debug, Clone)]
+struct GroupBadge {
+    group: usize,
+    lines: Range<usize>,  // 1-based, end exclusive
+    items: ItemSet,
 }
 
 
-fn create_hash_set_of_chars(string: &str) -> HashSet<char> {
-    HashSet::from_iter(string.chars())
-}
+fn get_group_badges(
+    input_str: &str, group_size: usize, table: &PriorityTable
+) -> Result<Vec<GroupBadge>, LineError> {
+    let rusacks: Vec<&str> = input_str.lines().collect();
 
+    rusacks.chunks(group_size).enumerate().map(|(group, rusack_chunk)| {
+        let first_line: usize = group * group_size + 1;
+        let items: ItemSet = rusack_chunk.iter().enumerate()
+            .map(|(i, rusack)| parse_rusack(rusack, first_line + i, table))
+            .collect::<Result<ItemSet, LineError>>()?;
 
-fn get_element_in_common(set_a: &HashSet<char>, set_b: &HashSet<char>) -> char {
-    set_a.intersection(&set_b).map(|x| *x).next().expect("Error!")
+        Ok(GroupBadge {
+            group: group + 1,
+            lines: first_line..first_line + rusack_chunk.len(),
+            items,
+        })
+    }).collect()
 }
 
 
-fn get_intersection(set_a: &HashSet<char>, set_b: &HashSet<char>) -> HashSet<char> {
-    set_a.intersection(&set_b).map(|x| *x).collect()
+fn get_badges_priorities_sum(badges: &[GroupBadge], table: &PriorityTable) -> u64 {
+    // Groups without exactly one badge have no meaningful priority, so they're skipped
+    badges.iter()
+        .filter(|badge| badge.items.len() == 1)
+        .filter_map(|badge| badge.items.iter().next())
+        .map(|item| table.get_priority(item))
+        .sum()
 }
 
 
-fn get_priority_value(item: char) -> u64 {
-    let item_ascii: u32 = item as u32;
-
-    // We need to do this check because the ASCII values of uppercase letters is
-    // actually lesser than the values of lowercases
-    if item_ascii >= LOWERCASE_OFFSET {
-        return (item_ascii - LOWERCASE_OFFSET + 1).into()
-    }
+// Halves by number of characters, so multi-byte items aren't cut in the middle
+fn split_string_in_half(string: &str) -> (&str, &str) {
+    let middle: usize = string.char_indices()
+        .nth(string.chars().count() / 2)
+        .map_or(string.len(), |(i, _)| i);
 
-    (item_ascii - UPPERCASE_OFFSET + N_OF_LETTERS + 1).into()
+    (&string[..middle], &string[middle..])
 }
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+use aoc_common::LineError;
+
+use crate::item_set::MAX_ITEMS;
+
+pub const DEFAULT_PRIORITIES: &str = "letters";
+
+// Each line gives a symbol, or an inclusive range of symbols, and its (first) priority
+pub const PRESETS: [(&str, &str); 3] = [
+    ("letters", "\
+        a-z 1\n\
+        A-Z 27\n\
+    "),
+    ("alphanumeric", "\
+        a-z 1\n\
+        A-Z 27\n\
+        0-9 53\n\
+    "),
+    ("digits", "\
+        0-9 0\n\
+    "),
+];
+const N_OF_ASCII: usize = 128;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownItem {
+    pub item: char,
+    pub column: usize,  // 1-based, in characters
+}
+
+impl fmt::Display for UnknownItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown item {:?} at column {}", self.item, self.column)
+    }
+}
+
+
+// Items known by a table are numbered in the order they were defined, which is also the
+// bit they take in an `ItemSet`
+#[derive(Debug, Clone)]
+pub struct PriorityTable {
+    symbols: Vec<char>,
+    priorities: Vec<u64>,
+    // Most inputs are ASCII, and an array is much faster than hashing every item
+    ascii_indexes: [Option<u8>; N_OF_ASCII],
+    other_indexes: HashMap<char, usize>,
+}
+
+impl PriorityTable {
+    // Either the name of a preset or the path of a file with a priority table
+    pub fn load(name_or_path: &str) -> Result<PriorityTable, String> {
+        match PRESETS.iter().find(|(name, _)| *name == name_or_path) {
+            Some((_, table)) => {
+                PriorityTable::from_table(table).map_err(|error| error.to_string())
+            },
+            None => {
+                let table: String = fs::read_to_string(name_or_path)
+                    .map_err(|error| format!("Unable to read {name_or_path}: {error}"))?;
+                PriorityTable::from_table(&table)
+                    .map_err(|error| format!("{name_or_path}, {error}"))
+            },
+        }
+    }
+
+    // Each line is either `<symbol> <priority>` or `<first>-<last> <first priority>`, the
+    // latter giving consecutive priorities to the range; blank lines and `#` comments are skipped
+    pub fn from_table(table: &str) -> Result<PriorityTable, LineError> {
+        let mut priority_table: PriorityTable = PriorityTable {
+            symbols: Vec::new(),
+            priorities: Vec::new(),
+            ascii_indexes: [None; N_OF_ASCII],
+            other_indexes: HashMap::new(),
+        };
+
+        let lines: Vec<&str> = table.lines().collect();
+        let error = |line: usize, reason: &str| LineError {
+            line: line + 1,
+            content: String::from(*lines.get(line).unwrap_or(&"")),
+            reason: String::from(reason),
+        };
+
+        for (i, line) in lines.iter().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (symbols, priority): (&str, &str) = match words.as_slice() {
+                [] => continue,
+                [comment, ..] if comment.starts_with('#') => continue,
+                [symbols, priority] => (symbols, priority),
+                _ => return Err(error(i, "expected a symbol (or a range) and a priority")),
+            };
+
+            let priority: u64 = priority.parse().map_err(|_| error(i, "invalid priority"))?;
+            let chars: Vec<char> = symbols.chars().collect();
+            let range: Vec<char> = match chars.as_slice() {
+                [symbol] => vec![*symbol],
+                [first, '-', last] if first <= last => (*first..=*last).collect(),
+                _ => return Err(error(i, "expected a symbol or a range like a-z")),
+            };
+
+            for (offset, symbol) in range.into_iter().enumerate() {
+                if priority_table.get_index(symbol).is_some() {
+                    return Err(error(i, &format!("{symbol:?} already has a priority")));
+                }
+                if priority_table.symbols.len() == MAX_ITEMS {
+                    return Err(error(i, &format!("tables are limited to {MAX_ITEMS} symbols")));
+                }
+                priority_table.insert(symbol, priority + offset as u64);
+            }
+        }
+
+        if priority_table.symbols.is_empty() {
+            return Err(error(lines.len().saturating_sub(1), "the table has no symbols"));
+        }
+
+        Ok(priority_table)
+    }
+
+    pub fn letters() -> PriorityTable {
+        PriorityTable::load(DEFAULT_PRIORITIES).expect("The presets are valid")
+    }
+
+    fn insert(&mut self, symbol: char, priority: u64) {
+        let index: usize = self.symbols.len();
+        match self.ascii_indexes.get_mut(symbol as usize) {
+            Some(ascii_index) => *ascii_index = Some(index as u8),
+            None => { self.other_indexes.insert(symbol, index); },
+        }
+
+        self.symbols.push(symbol);
+        self.priorities.push(priority);
+    }
+
+    pub fn get_index(&self, item: char) -> Option<usize> {
+        match self.ascii_indexes.get(item as usize) {
+            Some(index) => index.map(usize::from),
+            None => self.other_indexes.get(&item).copied(),
+        }
+    }
+
+    pub fn get_symbol(&self, index: usize) -> char {
+        self.symbols[index]
+    }
+
+    pub fn get_priority(&self, index: usize) -> u64 {
+        self.priorities[index]
+    }
+}
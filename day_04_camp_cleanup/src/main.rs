@@ -1,25 +1,240 @@
+use std::env;
+use std::fmt;
 use std::fs;
 
-use regex;
+use aoc_common::{exit_with_error, Interval, IntervalSet, LineError};
+
+mod optimizer;
+mod sweep;
 
 const INPUT_FILE_PATH: &str = "input";
-const INPUT_LINE_REGEX: &str = r"(\d+)-(\d+),(\d+)-(\d+)";
+const DEFAULT_MIN_COVERAGE: usize = 2;
+
+const USAGE: &str = "Usage: [lines | coverage [--universe <a-b>] [--min <k>] | sweep [--list] \
+    | optimize [--output <file>]]";
 
 fn main() {
     let input: String = read_input_file(INPUT_FILE_PATH);
-    let line_regex: regex::Regex = regex::Regex::new(INPUT_LINE_REGEX).unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
 
+    let assignments: Vec<Assignment> = get_assignments(&input)
+        .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+
+    match args.first().map(|x| x.as_str()) {
+        None => print_overlap_counts(&assignments),
+        Some("lines") => get_lines(&assignments).for_each(|x| println!("{}", LineStats::new(x))),
+        Some("coverage") => print_coverage(&assignments, &args[1..]),
+        Some("sweep") => print_sweep(&assignments, &args[1..]),
+        Some("optimize") => print_reassignments(&assignments, &args[1..]),
+        _ => exit_with_error(USAGE),
+    }
+}
+
+// Lines with two elves are counted as in the puzzle; any other line gets its own statistics
+fn print_overlap_counts(assignments: &[Assignment]) {
     let mut n_full_overlaps: u32 = 0;
     let mut n_partial_overlaps: u32 = 0;
+    let mut other_lines: Vec<LineStats> = Vec::new();
 
-    for capture in line_regex.captures_iter(&input) {
-        let (interval_a, interval_b): (Interval, Interval) = build_intervals(capture);
-        n_full_overlaps += check_full_overlap(&interval_a, &interval_b) as u32;
-        n_partial_overlaps += check_partial_overlap(&interval_a, &interval_b) as u32;
+    for line in get_lines(assignments) {
+        match line {
+            [elf_a, elf_b] => {
+                let (interval_a, interval_b): (&Interval<u32>, &Interval<u32>) =
+                    (&elf_a.sections, &elf_b.sections);
+                n_full_overlaps += check_full_overlap(interval_a, interval_b) as u32;
+                n_partial_overlaps += check_partial_overlap(interval_a, interval_b) as u32;
+            },
+            _ => other_lines.push(LineStats::new(line)),
+        }
     }
 
     println!("The number of full overlapping intervals is {n_full_overlaps}.");
     println!("The number of partial overlapping intervals is {n_partial_overlaps}.");
+
+    if !other_lines.is_empty() {
+        println!("Lines without exactly two elves:");
+        other_lines.iter().for_each(|stats| println!("{stats}"));
+    }
+}
+
+struct LineStats<'a> {
+    assignments: &'a [Assignment],
+    n_overlapping_pairs: u64,
+    container: Option<&'a Assignment>,  // One that contains every other assignment
+    shared: Option<Interval<u32>>,      // Sections in every assignment
+}
+
+impl<'a> LineStats<'a> {
+    fn new(assignments: &'a [Assignment]) -> LineStats<'a> {
+        LineStats {
+            assignments,
+            n_overlapping_pairs: sweep::count_overlapping_pairs(assignments),
+            container: assignments.iter()
+                .find(|x| assignments.iter().all(|y| x.sections.contains(&y.sections))),
+            shared: assignments.iter()
+                .try_fold(assignments[0].sections, |shared, x| shared.intersection(&x.sections)),
+        }
+    }
+}
+
+impl fmt::Display for LineStats<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranges: Vec<String> = self.assignments.iter().map(|x| x.sections.to_string()).collect();
+        write!(
+            f, "line {} ({}): {} elves, {} overlapping pairs, ",
+            self.assignments[0].line, ranges.join(","), self.assignments.len(),
+            self.n_overlapping_pairs
+        )?;
+
+        match self.container {
+            Some(container) => write!(f, "elf {} contains all the others, ", container.elf)?,
+            None => write!(f, "no elf contains all the others, ")?,
+        }
+        match self.shared {
+            Some(shared) => write!(f, "all share {shared}"),
+            None => write!(f, "no section is shared by all"),
+        }
+    }
+}
+
+// Sections nobody cleans, and sections cleaned by `k` or more elves, within the universe
+// (by default, from the first to the last section assigned to anyone)
+fn print_coverage(assignments: &[Assignment], args: &[String]) {
+    let mut universe: Option<Interval<u32>> = None;
+    let mut min_coverage: usize = DEFAULT_MIN_COVERAGE;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--universe", Some(x)) => x.parse().ok().map(|x| universe = Some(x)),
+            ("--min", Some(x)) => x.parse().ok().filter(|x| *x > 0).map(|x| min_coverage = x),
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            exit_with_error(USAGE);
+        }
+    }
+
+    let assignments: Vec<Interval<u32>> = assignments.iter().map(|x| x.sections).collect();
+    let covered: IntervalSet<u32> = assignments.iter().copied().collect();
+    let universe: Interval<u32> = match (universe, covered.intervals()) {
+        (Some(universe), _) => universe,
+        (None, [first, .., last]) | (None, [first @ last]) => {
+            Interval::new(first.start(), last.end()).unwrap()
+        },
+        (None, []) => return println!("There are no assignments."),
+    };
+
+    let uncovered: IntervalSet<u32> = covered.complement(&universe);
+    let redundant: IntervalSet<u32> = IntervalSet::covered_at_least(&assignments, min_coverage)
+        .intersection(&[universe].into_iter().collect());
+
+    println!("Universe: {universe} ({} sections, {} elves)", universe.length(), assignments.len());
+    println!("Covered by nobody: {}", describe_sections(&uncovered));
+    println!("Covered by at least {min_coverage} elves: {}", describe_sections(&redundant));
+}
+
+fn describe_sections(sections: &IntervalSet<u32>) -> String {
+    let ranges: Vec<String> = sections.intervals().iter().map(|x| x.to_string()).collect();
+    match sections.is_empty() {
+        true => String::from("0 sections"),
+        false => format!(
+            "{} sections in {} ranges: {}", sections.length(), ranges.len(), ranges.join(", ")
+        ),
+    }
+}
+
+// Treats every assignment of the input as one population, regardless of the line it's in
+fn print_sweep(assignments: &[Assignment], args: &[String]) {
+    let list_pairs: bool = match args {
+        [] => false,
+        [flag] if flag == "--list" => true,
+        _ => exit_with_error(USAGE),
+    };
+
+    if list_pairs {
+        sweep::for_each_overlapping_pair(assignments, |a, b| {
+            let (a, b): (&Assignment, &Assignment) = (&assignments[a], &assignments[b]);
+            match (a.sections.contains(&b.sections), b.sections.contains(&a.sections)) {
+                (true, true) => println!("{a} is the same as {b}"),
+                (true, false) => println!("{a} contains {b}"),
+                (false, true) => println!("{b} contains {a}"),
+                (false, false) => println!("{a} overlaps {b}"),
+            }
+        });
+    }
+
+    let (max_overlap, max_sections): (usize, IntervalSet<u32>) =
+        sweep::get_max_overlap(assignments);
+    let max_ranges: Vec<String> = max_sections.intervals().iter().map(|x| x.to_string()).collect();
+
+    println!("There are {} assignments.", assignments.len());
+    println!("{} pairs of assignments overlap.", sweep::count_overlapping_pairs(assignments));
+    println!(
+        "{} pairs have one assignment containing the other.",
+        sweep::count_contained_pairs(assignments)
+    );
+    println!("At most {max_overlap} elves overlap, at sections {}.", max_ranges.join(", "));
+}
+
+// Trims the ranges of every line so that no section is cleaned twice, keeping the sections
+// that get cleaned; elves left without work are removed from their line. Lines with too many
+// elves to search are kept as they are, with a warning.
+fn print_reassignments(assignments: &[Assignment], args: &[String]) {
+    let output: Option<&str> = match args {
+        [] => None,
+        [flag, path] if flag == "--output" => Some(path),
+        _ => exit_with_error(USAGE),
+    };
+
+    let mut reassignments: String = String::new();
+    let (mut length_before, mut length_after): (u64, u64) = (0, 0);
+    let (mut n_changed, mut n_dropped): (usize, usize) = (0, 0);
+
+    for line in get_lines(assignments) {
+        let ranges: Vec<Interval<u32>> = line.iter().map(|x| x.sections).collect();
+        let reassignment: optimizer::Reassignment = match line.len() {
+            n if n > optimizer::MAX_ELVES_PER_LINE => {
+                eprintln!(
+                    "Warning: line {} has more than {} elves, so it's kept unchanged",
+                    line[0].line, optimizer::MAX_ELVES_PER_LINE
+                );
+                optimizer::Reassignment {
+                    sections: ranges.iter().copied().map(Some).collect(),
+                    n_changed: 0,
+                }
+            },
+            _ => optimizer::reassign(&ranges),
+        };
+        let kept: Vec<Interval<u32>> = reassignment.sections.iter().flatten().copied().collect();
+
+        length_before += ranges.iter().map(|x| x.length()).sum::<u64>();
+        length_after += kept.iter().map(|x| x.length()).sum::<u64>();
+        n_changed += reassignment.n_changed;
+        n_dropped += ranges.len() - kept.len();
+
+        let kept: Vec<String> = kept.iter().map(|x| x.to_string()).collect();
+        reassignments.push_str(&kept.join(","));
+        reassignments.push('\n');
+    }
+
+    println!(
+        "The total assigned length goes from {length_before} to {length_after} sections, \
+        changing {n_changed} of {} elves ({n_dropped} left without work).",
+        assignments.len()
+    );
+
+    match output {
+        Some(path) => {
+            fs::write(path, reassignments).unwrap_or_else(|error| {
+                exit_with_error(&format!("Unable to write {path}: {error}"))
+            });
+            println!("The new assignments were written to {path}.");
+        },
+        None => print!("{reassignments}"),
+    }
 }
 
 fn read_input_file(file_path: &str) -> String {
@@ -30,41 +245,46 @@ fn read_input_file(file_path: &str) -> String {
     file_contents
 }
 
-fn build_intervals(capture: regex::Captures) -> (Interval, Interval) {
-    (
-        Interval {
-            start: capture[1].parse().unwrap(),
-            end: capture[2].parse().unwrap(),
-        },
-        Interval {
-            start: capture[3].parse().unwrap(),
-            end: capture[4].parse().unwrap(),
-        },
-    )
+struct Assignment {
+    line: usize,  // 1-based
+    elf: usize,   // 1-based, within the line
+    sections: Interval<u32>,
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} elf {} ({})", self.line, self.elf, self.sections)
+    }
 }
 
-fn check_full_overlap(interval_a: &Interval, interval_b: &Interval) -> bool {
-    let start_difference: i32 = interval_a.start as i32 - interval_b.start as i32;
-    let end_difference: i32 = interval_a.end as i32 - interval_b.end as i32;
+// Every elf of every line, in order; lines are comma-separated ranges like `2-4,6-8`, and
+// blank lines are skipped
+fn get_assignments(input: &str) -> Result<Vec<Assignment>, LineError> {
+    let mut assignments: Vec<Assignment> = Vec::new();
 
-    if (start_difference >= 0) && (end_difference <= 0) {
-        return true;
-    } else if (start_difference <= 0) && (end_difference >= 0) {
-        return true;
+    for (i, line) in input.lines().enumerate().filter(|(_, x)| !x.trim().is_empty()) {
+        for (j, range) in line.split(',').enumerate() {
+            let sections: Interval<u32> = range.trim().parse().map_err(|error| LineError {
+                line: i + 1,
+                content: String::from(line),
+                reason: format!("elf {}: {error}", j + 1),
+            })?;
+            assignments.push(Assignment { line: i + 1, elf: j + 1, sections });
+        }
     }
 
-    false
+    Ok(assignments)
 }
 
-fn check_partial_overlap(interval_a: &Interval, interval_b: &Interval) -> bool {
-    if (interval_a.start > interval_b.end) || (interval_a.end < interval_b.start) {
-        return false
-    }
+// The assignments of each line
+fn get_lines(assignments: &[Assignment]) -> impl Iterator<Item = &[Assignment]> {
+    assignments.chunk_by(|a, b| a.line == b.line)
+}
 
-    true
+fn check_full_overlap(interval_a: &Interval<u32>, interval_b: &Interval<u32>) -> bool {
+    interval_a.contains(interval_b) || interval_b.contains(interval_a)
 }
 
-struct Interval {
-    start: u8,
-    end: u8,
+fn check_partial_overlap(interval_a: &Interval<u32>, interval_b: &Interval<u32>) -> bool {
+    interval_a.overlaps(interval_b)
 }
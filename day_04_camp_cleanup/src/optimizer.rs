@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+
+use aoc_common::Interval;
+
+// The sweep tracks which of the elves with overlapping ranges already got work, so its time
+// grows exponentially with them; 14 elves all overlapping take around a second
+pub const MAX_ELVES_PER_LINE: usize = 14;
+
+
+// New range of every elf of a line, `None` for the ones left without work
+#[derive(Debug, Clone)]
+pub struct Reassignment {
+    pub sections: Vec<Option<Interval<u32>>>,
+    pub n_changed: usize,
+}
+
+
+// Pieces of the union of the ranges that no range starts or ends inside of, each one split
+// further so that every elf covering it could get a section of it
+struct Segment {
+    start: u32,
+    end: u32,
+    elves: Vec<usize>,  // The elves whose range covers the segment
+}
+
+fn get_segments(ranges: &[Interval<u32>]) -> Vec<Segment> {
+    // Computed in u64, since the bound after `u32::MAX` doesn't fit
+    let mut bounds: Vec<u64> = ranges.iter()
+        .flat_map(|x| [x.start() as u64, x.end() as u64 + 1])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    bounds.windows(2).flat_map(|pair| {
+        let (start, end): (u32, u32) = (pair[0] as u32, (pair[1] - 1) as u32);
+        let elves: Vec<usize> = (0..ranges.len())
+            .filter(|elf| ranges[*elf].start() <= start && end <= ranges[*elf].end())
+            .collect();
+
+        // Only the order of the elves working on a piece matters, not where they switch, so
+        // single sections and then the rest are enough
+        let n_pieces: u32 = (elves.len() as u64).min(pair[1] - pair[0]) as u32;
+        (0..n_pieces).map(move |i| Segment {
+            start: start + i,
+            end: if i + 1 == n_pieces { end } else { start + i },
+            elves: elves.clone(),
+        })
+    }).collect()
+}
+
+
+// Sweep state right after a segment: the elf working on it, the elves that already got some
+// work (so they can't get a second, separate piece) and whether the current elf started at
+// the beginning of its range, i.e. whether it can still end up unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct State {
+    owner: usize,
+    started: u64,
+    intact: bool,
+}
+
+
+// Elves whose range changes, and among them, the ones left without any work. Compared in
+// that order, so that among the best solutions, trimming an elf is preferred to dropping it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Cost {
+    n_changed: usize,
+    n_dropped: usize,
+}
+
+impl Cost {
+    fn add(&self, n_changed: usize, n_dropped: usize) -> Cost {
+        Cost { n_changed: self.n_changed + n_changed, n_dropped: self.n_dropped + n_dropped }
+    }
+}
+
+
+type Layer = BTreeMap<State, (Cost, Option<State>)>;
+
+
+// Removing duplicated work means every section of the union goes to exactly one elf, so the
+// total length is always that of the union; what's left to minimize is the number of elves
+// whose range changes. Dynamic programming over the segments of the union, where each elf
+// works on consecutive segments; elves are only tracked while their range lasts.
+pub fn reassign(ranges: &[Interval<u32>]) -> Reassignment {
+    assert!(ranges.len() <= MAX_ELVES_PER_LINE, "Too many elves in a single line");
+
+    let segments: Vec<Segment> = get_segments(ranges);
+    let first_segment: Vec<usize> = (0..ranges.len())
+        .map(|elf| segments.iter().position(|x| x.elves.contains(&elf)).unwrap())
+        .collect();
+    let last_segment: Vec<usize> = (0..ranges.len())
+        .map(|elf| segments.iter().rposition(|x| x.elves.contains(&elf)).unwrap())
+        .collect();
+
+    // Elves are settled at the last segment of their range: those that never worked were
+    // dropped, and the one working stays unchanged only if it worked on the whole range
+    let settle_dropped = |state: &State, segment: usize, cost: Cost| -> Cost {
+        let n_dropped: usize = (0..ranges.len())
+            .filter(|elf| last_segment[*elf] == segment && state.started & (1 << elf) == 0)
+            .count();
+        cost.add(n_dropped, n_dropped)
+    };
+    let settle_owner = |state: &State, segment: usize, cost: Cost| -> Cost {
+        cost.add(!(state.intact && last_segment[state.owner] == segment) as usize, 0)
+    };
+
+    let mut layers: Vec<Layer> = vec![segments[0].elves.iter()
+        .map(|elf| {
+            let state: State = State { owner: *elf, started: 1 << elf, intact: true };
+            (state, (Cost::default(), None))
+        })
+        .collect()];
+
+    for (i, segment) in segments.iter().enumerate().skip(1) {
+        let mut layer: Layer = BTreeMap::new();
+        // Elves whose range is over don't need to be tracked anymore
+        let ended: u64 = (0..ranges.len())
+            .filter(|elf| last_segment[*elf] == i - 1)
+            .fold(0, |mask, elf| mask | 1 << elf);
+
+        for (state, (cost, _)) in layers[i - 1].iter() {
+            let cost: Cost = settle_dropped(state, i - 1, *cost);
+
+            for elf in segment.elves.iter().copied() {
+                let (next, next_cost): (State, Cost) = match elf == state.owner {
+                    true => (State { started: state.started & !ended, ..*state }, cost),
+                    false if state.started & (1 << elf) != 0 => continue,
+                    false => (
+                        State {
+                            owner: elf,
+                            started: (state.started | 1 << elf) & !ended,
+                            intact: first_segment[elf] == i,
+                        },
+                        settle_owner(state, i - 1, cost),
+                    ),
+                };
+
+                if layer.get(&next).is_none_or(|(x, _)| next_cost < *x) {
+                    layer.insert(next, (next_cost, Some(*state)));
+                }
+            }
+        }
+
+        layers.push(layer);
+    }
+
+    let last: usize = segments.len() - 1;
+    let (mut state, cost): (State, Cost) = layers[last].iter()
+        .map(|(state, (cost, _))| {
+            (*state, settle_owner(state, last, settle_dropped(state, last, *cost)))
+        })
+        .min_by_key(|(_, cost)| *cost)
+        .unwrap();
+
+    let mut sections: Vec<Option<Interval<u32>>> = vec![None; ranges.len()];
+    for i in (0..segments.len()).rev() {
+        let segment: &Segment = &segments[i];
+        sections[state.owner] = match sections[state.owner] {
+            Some(x) => Interval::new(segment.start, x.end()),
+            None => Interval::new(segment.start, segment.end),
+        };
+        state = match layers[i][&state].1 {
+            Some(previous) => previous,
+            None => break,
+        };
+    }
+
+    Reassignment { sections, n_changed: cost.n_changed }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every way of giving each elf a piece of its range or nothing, keeping the pieces
+    // disjoint; the ones that cover the whole union are compared by cost
+    fn brute_force(ranges: &[Interval<u32>], elf: usize, taken: u64, cost: Cost) -> Option<Cost> {
+        let Some(range) = ranges.get(elf) else {
+            let union: u64 = ranges.iter()
+                .flat_map(|x| x.start()..=x.end())
+                .fold(0, |mask, x| mask | 1 << x);
+            return (taken == union).then_some(cost);
+        };
+
+        let dropped: Option<Cost> = brute_force(ranges, elf + 1, taken, cost.add(1, 1));
+        let trimmed = (range.start()..=range.end())
+            .flat_map(|start| (start..=range.end()).map(move |end| (start, end)))
+            .filter_map(|(start, end)| {
+                let piece: u64 = (start..=end).fold(0, |mask, x| mask | 1 << x);
+                let changed: usize = usize::from((start, end) != (range.start(), range.end()));
+                (taken & piece == 0)
+                    .then(|| brute_force(ranges, elf + 1, taken | piece, cost.add(changed, 0)))
+                    .flatten()
+            });
+
+        dropped.into_iter().chain(trimmed).min()
+    }
+
+    fn check_line(ranges: &[Interval<u32>]) {
+        let reassignment: Reassignment = reassign(ranges);
+        let kept: Vec<Interval<u32>> = reassignment.sections.iter().flatten().copied().collect();
+
+        let mut pieces: u64 = 0;
+        for (section, range) in reassignment.sections.iter().zip(ranges) {
+            if let Some(section) = section {
+                let piece: u64 = (section.start()..=section.end()).fold(0, |x, y| x | 1 << y);
+                assert!(range.contains(section), "{section} is outside of {range}");
+                assert_eq!(pieces & piece, 0, "{section} overlaps another piece in {ranges:?}");
+                pieces |= piece;
+            }
+        }
+        let n_changed: usize = reassignment.sections.iter().zip(ranges)
+            .filter(|(section, range)| section.as_ref() != Some(range))
+            .count();
+        assert_eq!(n_changed, reassignment.n_changed);
+
+        let cost: Cost = Cost { n_changed, n_dropped: ranges.len() - kept.len() };
+        let expected: Option<Cost> = brute_force(ranges, 0, 0, Cost::default());
+        assert_eq!(Some(cost), expected, "not optimal for {ranges:?}");
+    }
+
+    #[test]
+    fn reassign_is_optimal_for_every_line_of_three_elves() {
+        let intervals: Vec<Interval<u32>> = (1..=5)
+            .flat_map(|start| (start..=5).filter_map(move |end| Interval::new(start, end)))
+            .collect();
+
+        for a in intervals.iter() {
+            for b in intervals.iter() {
+                for c in intervals.iter() {
+                    check_line(&[*a, *b, *c]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reassign_is_optimal_for_random_lines() {
+        // Small linear congruential generator, so the lines are the same on every run
+        let mut seed: u64 = 2022;
+        let mut next = |bound: u32| -> u32 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as u32 % bound
+        };
+
+        for _ in 0..300 {
+            let n_elves: u32 = 4 + next(3);
+            let ranges: Vec<Interval<u32>> = (0..n_elves).map(|_| {
+                let (a, b): (u32, u32) = (1 + next(7), 1 + next(7));
+                Interval::new(a.min(b), a.max(b)).unwrap()
+            }).collect();
+            check_line(&ranges);
+        }
+    }
+}
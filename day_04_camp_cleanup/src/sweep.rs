@@ -0,0 +1,110 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use aoc_common::{Interval, IntervalSet};
+
+use crate::Assignment;
+
+
+// Indexes of the assignments sorted by start, so that every interval that overlaps one
+// starting later is still active when the sweep reaches it
+fn get_start_order(assignments: &[Assignment]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..assignments.len()).collect();
+    order.sort_by_key(|i| {
+        let sections: Interval<u32> = assignments[*i].sections;
+        (sections.start(), Reverse(sections.end()))
+    });
+    order
+}
+
+
+pub fn count_overlapping_pairs(assignments: &[Assignment]) -> u64 {
+    let mut active_ends: BinaryHeap<Reverse<u32>> = BinaryHeap::new();
+    let mut n_pairs: u64 = 0;
+
+    for i in get_start_order(assignments) {
+        let sections: Interval<u32> = assignments[i].sections;
+        while active_ends.peek().is_some_and(|Reverse(end)| *end < sections.start()) {
+            active_ends.pop();
+        }
+
+        n_pairs += active_ends.len() as u64;
+        active_ends.push(Reverse(sections.end()));
+    }
+
+    n_pairs
+}
+
+
+// Every overlapping pair, the one that starts first (or is longer) first. Listing is
+// proportional to the number of pairs, since every active assignment makes one.
+pub fn for_each_overlapping_pair(assignments: &[Assignment], mut f: impl FnMut(usize, usize)) {
+    let mut active: Vec<usize> = Vec::new();
+
+    for i in get_start_order(assignments) {
+        let start: u32 = assignments[i].sections.start();
+        active.retain(|x| assignments[*x].sections.end() >= start);
+
+        active.iter().for_each(|x| f(*x, i));
+        active.push(i);
+    }
+}
+
+
+// Pairs where one assignment contains the other. In start order (longest first on ties),
+// an assignment contains a later one exactly when it doesn't end before it, so a Fenwick
+// tree over the ends counts them; identical assignments are a single pair.
+pub fn count_contained_pairs(assignments: &[Assignment]) -> u64 {
+    let mut ends: Vec<u32> = assignments.iter().map(|x| x.sections.end()).collect();
+    ends.sort_unstable();
+    ends.dedup();
+
+    let mut tree: Vec<u64> = vec![0; ends.len() + 1];
+    let mut n_pairs: u64 = 0;
+
+    for (n_inserted, i) in get_start_order(assignments).into_iter().enumerate() {
+        // 1-based rank of the end, as Fenwick trees need
+        let rank: usize = ends.partition_point(|x| *x < assignments[i].sections.end()) + 1;
+
+        let mut n_ending_before: u64 = 0;
+        let mut node: usize = rank - 1;
+        while node > 0 {
+            n_ending_before += tree[node];
+            node &= node - 1;
+        }
+        n_pairs += n_inserted as u64 - n_ending_before;
+
+        let mut node: usize = rank;
+        while node < tree.len() {
+            tree[node] += 1;
+            node += node & node.wrapping_neg();
+        }
+    }
+
+    n_pairs
+}
+
+
+// Most assignments covering a single section, and the sections where that happens
+pub fn get_max_overlap(assignments: &[Assignment]) -> (usize, IntervalSet<u32>) {
+    let intervals: Vec<Interval<u32>> = assignments.iter().map(|x| x.sections).collect();
+
+    let mut bounds: Vec<(u32, bool)> = intervals.iter()
+        .flat_map(|x| [(x.start(), false), (x.end(), true)])
+        .collect();
+    bounds.sort_unstable();
+
+    let mut count: usize = 0;
+    let mut max_count: usize = 0;
+    for (_, is_end) in bounds {
+        match is_end {
+            false => {
+                count += 1;
+                max_count = max_count.max(count);
+            },
+            true => count -= 1,
+        }
+    }
+
+    (max_count, IntervalSet::covered_at_least(&intervals, max_count.max(1)))
+}
@@ -0,0 +1,88 @@
+pub const CRANE_NAMES: [&str; 2] = ["9000", "9001"];
+
+
+// Stacks are indexed from zero here; the move syntax of the input starts at one
+pub trait Crane {
+    fn name(&self) -> String;
+    fn move_crates(&self, stacks: &mut [Vec<String>], n: usize, from: usize, to: usize);
+
+    // Whether moving `n` crates straight back after moving them restores their order
+    fn is_reversible(&self, _n: usize) -> bool {
+        true
+    }
+}
+
+
+// Moves one crate at a time, so they end up in reverse order
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn move_crates(&self, stacks: &mut [Vec<String>], n: usize, from: usize, to: usize) {
+        for _ in 0..n {
+            let crate_name: String = stacks[from].pop().unwrap();
+            stacks[to].push(crate_name);
+        }
+    }
+}
+
+
+// Moves all the crates at once, keeping their order
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
+    }
+
+    fn move_crates(&self, stacks: &mut [Vec<String>], n: usize, from: usize, to: usize) {
+        let from_index: usize = stacks[from].len() - n;
+        let mut from_clone: Vec<String> = vec![String::new(); n];
+
+        from_clone.clone_from_slice(&stacks[from][from_index..]);
+        stacks[to].append(&mut from_clone);
+        stacks[from].truncate(from_index);
+    }
+}
+
+
+// A CrateMover 9001 that can't lift more than a few crates: it moves them in lifts from the
+// top, each lift keeping its order. A limit of one behaves like the CrateMover 9000.
+pub struct LimitedCrane {
+    pub max_per_lift: usize,
+}
+
+impl Crane for LimitedCrane {
+    fn name(&self) -> String {
+        format!("CrateMover 9001 (up to {} crates per lift)", self.max_per_lift)
+    }
+
+    fn move_crates(&self, stacks: &mut [Vec<String>], n: usize, from: usize, to: usize) {
+        let mut remaining: usize = n;
+        while remaining > 0 {
+            let lift: usize = remaining.min(self.max_per_lift);
+            CrateMover9001.move_crates(stacks, lift, from, to);
+            remaining -= lift;
+        }
+    }
+
+    // The lifts land in reverse order, so they only come back the same when they're all full
+    fn is_reversible(&self, n: usize) -> bool {
+        n <= self.max_per_lift || n.is_multiple_of(self.max_per_lift)
+    }
+}
+
+
+pub fn create_crane(name: &str, max_per_lift: Option<usize>) -> Result<Box<dyn Crane>, String> {
+    match (name, max_per_lift) {
+        (_, Some(0)) => Err(String::from("A crane must lift at least one crate")),
+        ("9000", None) => Ok(Box::new(CrateMover9000)),
+        ("9000", Some(_)) => Err(String::from("The CrateMover 9000 lifts one crate at a time")),
+        ("9001", None) => Ok(Box::new(CrateMover9001)),
+        ("9001", Some(max_per_lift)) => Ok(Box::new(LimitedCrane { max_per_lift })),
+        _ => Err(format!("Unknown crane '{name}', expected one of: {}", CRANE_NAMES.join(", "))),
+    }
+}
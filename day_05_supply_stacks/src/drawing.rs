@@ -0,0 +1,211 @@
+use std::ops::Range;
+
+use aoc_common::{split_records, LineError, Record};
+
+const CRATE_TEXT_LEN: usize = 3;
+
+
+// The last line of the drawing numbers the stacks, and the columns of its labels decide which
+// stack each crate is on, so neither the spacing nor the width of the crates is fixed
+pub fn parse_stacks(drawing: &Record) -> Result<Vec<Vec<String>>, LineError> {
+    let Some((number_line, rows)) = drawing.lines.split_last() else {
+        return Ok(Vec::new());
+    };
+    let error = |i: usize, line: &str, reason: String| LineError {
+        line: drawing.first_line + i,
+        content: String::from(line),
+        reason,
+    };
+
+    let labels: Vec<Range<usize>> = parse_labels(number_line)
+        .map_err(|reason| error(rows.len(), number_line, reason))?;
+    let mut stacks: Vec<Vec<String>> = vec![Vec::new(); labels.len()];
+
+    // From the bottom, so that every crate can be checked to be on top of another one
+    for (level, (i, row)) in rows.iter().enumerate().rev().enumerate() {
+        for (column, crate_name) in parse_crates(row).map_err(|reason| error(i, row, reason))? {
+            let span: Range<usize> = column..column + crate_name.chars().count() + 2;
+            let below: Vec<usize> = labels.iter().enumerate()
+                .filter(|(_, label)| label.start < span.end && span.start < label.end)
+                .map(|(stack, _)| stack)
+                .collect();
+
+            let reason: String = match below.as_slice() {
+                [stack] if stacks[*stack].len() == level => {
+                    stacks[*stack].push(crate_name);
+                    continue;
+                },
+                [stack] if stacks[*stack].len() > level => {
+                    format!("stack {} already has a crate in this row", stack + 1)
+                },
+                [_] => String::from("there's nothing below it"),
+                [] => String::from("it isn't above any stack label"),
+                _ => String::from("it's above more than one stack label"),
+            };
+            return Err(error(i, row, format!("crate at column {}: {reason}", column + 1)));
+        }
+    }
+
+    Ok(stacks)
+}
+
+
+// Columns of the stack labels, which must be 1, 2, 3... in order
+fn parse_labels(number_line: &str) -> Result<Vec<Range<usize>>, String> {
+    let mut labels: Vec<Range<usize>> = Vec::new();
+
+    for (column, word) in split_words(number_line) {
+        if word.parse::<usize>().ok() != Some(labels.len() + 1) {
+            return Err(format!(
+                "expected the stack label {} at column {}, in the last line of the drawing",
+                labels.len() + 1, column + 1
+            ));
+        }
+        labels.push(column..column + word.chars().count());
+    }
+
+    match labels.is_empty() {
+        true => Err(String::from("the last line of the drawing has no stack labels")),
+        false => Ok(labels),
+    }
+}
+
+
+// Crates of a row with their 0-based columns, where any name without spaces or brackets goes
+fn parse_crates(row: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut crates: Vec<(usize, String)> = Vec::new();
+
+    for (column, word) in split_words(row) {
+        let mut rest: &str = word;
+        let mut column: usize = column;
+
+        while !rest.is_empty() {
+            let name: &str = rest.strip_prefix('[')
+                .and_then(|x| x.split_once(']'))
+                .map(|(name, _)| name)
+                .filter(|name| !name.is_empty() && !name.contains('['))
+                .ok_or_else(|| format!("expected a crate like [A] at column {}", column + 1))?;
+
+            crates.push((column, String::from(name)));
+            rest = &rest[name.len() + 2..];
+            column += name.chars().count() + 2;
+        }
+    }
+
+    Ok(crates)
+}
+
+
+// Runs of non-whitespace characters with their 0-based columns, counted in characters
+fn split_words(line: &str) -> Vec<(usize, &str)> {
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+
+    for (column, (i, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column, i)),
+            (true, Some((word_column, word_start))) => {
+                words.push((word_column, &line[word_start..i]));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    words.extend(start.map(|(column, i)| (column, &line[i..])));
+    words
+}
+
+
+// Same format as the input: a row per height, from the top, where every stack takes the
+// width of its widest crate plus a space, and then the number line. Labels go right after the
+// opening bracket, which every crate of the stack covers. Rows keep their trailing spaces.
+pub fn render_stacks(stacks: &[Vec<String>]) -> String {
+    let label_width: usize = stacks.len().to_string().len();
+    let width: usize = stacks.iter().flatten()
+        .map(|x| x.chars().count() + 2)
+        .fold(CRATE_TEXT_LEN.max(label_width + 1), usize::max);
+    let height: usize = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+
+    let mut rows: Vec<String> = (0..height).rev().map(|level| {
+        let cells: Vec<String> = stacks.iter().map(|stack| match stack.get(level) {
+            Some(crate_name) => format!("{:<width$}", format!("[{crate_name}]")),
+            None => " ".repeat(width),
+        }).collect();
+        cells.join(" ")
+    }).collect();
+
+    let labels: Vec<String> = (1..=stacks.len())
+        .map(|i| format!(" {i:<0$}", width - 1))
+        .collect();
+    rows.push(labels.join(" "));
+
+    rows.join("\n") + "\n"
+}
+
+
+// Renders the stacks and checks that parsing the drawing gives them back
+pub fn render_checked(stacks: &[Vec<String>]) -> Result<String, String> {
+    let drawing: String = render_stacks(stacks);
+    let parsed: Vec<Vec<String>> = split_records(&drawing).first()
+        .map(parse_stacks)
+        .transpose()
+        .map_err(|error| format!("The drawing doesn't parse back: {error}"))?
+        .unwrap_or_default();
+
+    match parsed == stacks {
+        true => Ok(drawing),
+        false => Err(String::from("The drawing doesn't parse back into the same stacks")),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(stacks: &[Vec<&str>]) {
+        let stacks: Vec<Vec<String>> = stacks.iter()
+            .map(|stack| stack.iter().map(|x| String::from(*x)).collect())
+            .collect();
+        let drawing: String = render_checked(&stacks).unwrap();
+
+        // Without the trailing spaces, like an editor could leave it
+        let trimmed: String = drawing.lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect();
+        assert_eq!(parse_stacks(&split_records(&trimmed)[0]).unwrap(), stacks, "{drawing}");
+    }
+
+    #[test]
+    fn renders_the_input_format() {
+        let stacks: Vec<Vec<String>> = [vec!["Z", "N"], vec!["M", "C", "D"], vec!["P"]].iter()
+            .map(|stack| stack.iter().map(|x| String::from(*x)).collect())
+            .collect();
+        let drawing: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n";
+        assert_eq!(render_stacks(&stacks), drawing);
+    }
+
+    #[test]
+    fn round_trips_crates_of_mixed_widths() {
+        round_trip(&[vec!["ABCDEF"], vec!["A"]]);
+        round_trip(&[vec!["A"], vec!["ABCDEF", "B"], vec![], vec!["XY", "Z", "LONGEST"]]);
+        round_trip(&[vec!["Q"; 3], vec![], vec!["ab", "c"], vec!["d"], vec![], vec!["efg"]]);
+    }
+
+    #[test]
+    fn round_trips_more_than_nine_stacks() {
+        let stacks: Vec<Vec<&str>> = (0..12)
+            .map(|i| ["A", "BB", "CCC"].iter().copied().take(i % 4).collect())
+            .collect();
+        round_trip(&stacks);
+
+        let stacks: Vec<Vec<&str>> = (0..105).map(|i| vec!["X"; i % 3]).collect();
+        round_trip(&stacks);
+    }
+
+    #[test]
+    fn round_trips_no_crates() {
+        round_trip(&[vec![], vec![]]);
+    }
+}
@@ -1,43 +1,127 @@
+use std::env;
 use std::fs;
 
-use regex::Regex;
+mod crane;
+mod drawing;
+mod moves;
+mod planner;
+
+use aoc_common::{exit_with_error, LineError, Record, split_records};
+
+use crane::{Crane, CrateMover9000, CrateMover9001};
+use moves::{Move, MoveLog};
+use planner::Goal;
 
 const INPUT_FILE_PATH: &str = "input";
-const CRATE_REGEX: &str = r"\[(\w)\]";
-const MOVE_ACTION_REGEX: &str = r"move (\d+) from (\d+) to (\d+)";
-const STACKS_NUMBER_LINE_REGEX: &str = r"( \d+ )+";
+const DEFAULT_MAX_MOVES: usize = 6;
 
-const CRATE_TEXT_LEN: usize = 3;
+const USAGE: &str = "Usage: [repl | draw [start|end|<move>] [--output <file>] \
+    | plan (--target <drawing file> | --tops <crates>) [--max-moves <n>] [--output <file>]] \
+    [--crane 9000|9001] [--max-lift <n>]";
 
 
 fn main() {
     let input: String = read_input_file(INPUT_FILE_PATH);
-
-    let mut stacks: Vec<Vec<String>> = Vec::new();
-    let mut first_line: bool = true;
-
-    for line in input.lines() {
-        if is_stacks_number_line(line) {
-            break
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // The drawing and the moves are separated by a blank line; without moves, there's nothing
+    // after the drawing
+    let records: Vec<Record> = split_records(&input);
+    let drawing: &Record = records.first().unwrap_or_else(|| exit_with_error(&LineError {
+        line: 1,
+        content: String::new(),
+        reason: String::from("expected the drawing of the stacks"),
+    }.to_string()));
+    if let Some(extra) = records.get(2) {
+        exit_with_error(&LineError {
+            line: extra.first_line,
+            content: String::from(extra.lines[0]),
+            reason: String::from("expected no blank lines between the moves"),
+        }.to_string());
+    }
+    let stacks: Vec<Vec<String>> = drawing::parse_stacks(drawing)
+        .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+    let moves: Vec<Move> = records.get(1).map_or(Ok(Vec::new()), moves::parse_moves)
+        .unwrap_or_else(|error| exit_with_error(&error.to_string()));
+
+    let mode: Option<&str> = args.first().map(|x| x.as_str())
+        .filter(|x| ["repl", "draw", "plan"].contains(x));
+    let mut options: &[String] = if mode.is_some() { &args[1..] } else { &args };
+
+    let mut position: &str = "end";
+    if let (Some("draw"), Some(x)) = (mode, options.first()) {
+        if !x.starts_with("--") {
+            position = x;
+            options = &options[1..];
         }
+    }
 
-        if first_line {
-            stacks = generate_stacks_vector(line);
-            first_line = false;
-        }
+    let mut crane_name: Option<&str> = None;
+    let mut max_per_lift: Option<usize> = None;
+    let mut output: Option<&str> = None;
+    let mut goal: Option<Goal> = None;
+    let mut max_moves: usize = DEFAULT_MAX_MOVES;
+
+    let mut args_iter = options.iter();
+    while let Some(arg) = args_iter.next() {
+        let value: Option<&String> = args_iter.next();
+        let parsed: Option<()> = match (arg.as_str(), value) {
+            ("--crane", Some(x)) => { crane_name = Some(x); Some(()) },
+            ("--max-lift", Some(x)) => x.parse().ok().map(|x| max_per_lift = Some(x)),
+            ("--output", Some(x)) if matches!(mode, Some("draw" | "plan")) => {
+                output = Some(x);
+                Some(())
+            },
+            ("--target", Some(x)) if mode == Some("plan") => {
+                goal = Some(Goal::Stacks(read_target(x)));
+                Some(())
+            },
+            ("--tops", Some(x)) if mode == Some("plan") => { goal = Some(parse_tops(x)); Some(()) },
+            ("--max-moves", Some(x)) if mode == Some("plan") => {
+                x.parse().ok().map(|x| max_moves = x)
+            },
+            _ => None,
+        };
 
-        add_crates(&mut stacks, line);
+        if parsed.is_none() {
+            exit_with_error(USAGE);
+        }
+    }
+    if mode == Some("plan") && goal.is_none() {
+        exit_with_error(USAGE);
     }
 
-    // Reverse stack order so that .pop() and .push() works
-    for i in 0..stacks.len() {
-        stacks[i].reverse();
+    // Without a model, both parts of the puzzle are solved; other modes use the newer crane
+    let cranes: Vec<Box<dyn Crane>> = match (crane_name, max_per_lift) {
+        (None, None) if mode.is_some() => vec![Box::new(CrateMover9001)],
+        (None, None) => vec![Box::new(CrateMover9000), Box::new(CrateMover9001)],
+        (name, max_per_lift) => {
+            let crane: Box<dyn Crane> = crane::create_crane(name.unwrap_or("9001"), max_per_lift)
+                .unwrap_or_else(|error| exit_with_error(&error));
+            vec![crane]
+        },
+    };
+
+    match mode {
+        Some("repl") => return run_repl(&stacks, &moves, cranes[0].as_ref()),
+        Some("draw") => return draw_stacks(&stacks, &moves, cranes[0].as_ref(), position, output),
+        Some("plan") => {
+            let goal: Goal = goal.expect("The goal was checked with the options");
+            return plan_moves(&stacks, &goal, cranes[0].as_ref(), max_moves, output);
+        },
+        _ => {},
     }
 
-    input.lines().for_each(|l| move_crates_collectively(&mut stacks, l));
-    let top_crates: String = get_top_crates(&stacks);
+    for crane in cranes.iter() {
+        let (log, result): (MoveLog, Result<(), LineError>) =
+            apply_moves(crane.as_ref(), &stacks, &moves);
+        result.unwrap_or_else(|error| exit_with_error(&format!(
+            "The {} stopped after {} moves, {error}", crane.name(), log.n_applied()
+        )));
+        let top_crates: String = get_top_crates(log.stacks());
 
-    println!("The sequence of top crates is {top_crates}.");
+        println!("The sequence of top crates with the {} is {top_crates}.", crane.name());
+    }
 }
 
 
@@ -50,68 +134,115 @@ fn read_input_file(file_path: &str) -> String {
 }
 
 
-fn generate_stacks_vector(first_line: &str) -> Vec<Vec<String>> {
-    // Each crate occupies 3 spaces, and one space separates each crate
-    let n_stacks: usize = (first_line.len() + 1) / 4;
-    (0..n_stacks).map(|_| Vec::new()).collect::<Vec<Vec<String>>>()
-}
 
+// The drawing after some of the moves; with an output file, the remaining moves are written
+// after it, making a new input for the puzzle
+fn draw_stacks(
+    initial_stacks: &[Vec<String>], moves: &[Move], crane: &dyn Crane, position: &str,
+    output: Option<&str>
+) {
+    let n_applied: usize = match position {
+        "start" => 0,
+        "end" => moves.len(),
+        _ => match position.parse::<usize>() {
+            Ok(n) if n <= moves.len() => n,
+            _ => exit_with_error(&format!("Expected start, end or a move up to {}", moves.len())),
+        },
+    };
+
+    let (log, result): (MoveLog, Result<(), LineError>) =
+        apply_moves(crane, initial_stacks, &moves[..n_applied]);
+    let drawing: String = drawing::render_checked(log.stacks())
+        .unwrap_or_else(|error| exit_with_error(&error));
 
-fn is_stacks_number_line(line: &str) -> bool {
-    let number_line_regex: Regex = Regex::new(STACKS_NUMBER_LINE_REGEX).unwrap();
-    match number_line_regex.captures(line) {
-        Some(_) => true,
-        None => false,
+    // Shows how far the crane got before failing, but doesn't write a broken input
+    if let Err(error) = result {
+        print!("{drawing}");
+        exit_with_error(&format!("Stopped after {} moves, {error}", log.n_applied()));
     }
-}
 
+    match output {
+        Some(path) => {
+            let remaining: String = moves[n_applied..].iter().map(|l| format!("{l}\n")).collect();
+            fs::write(path, format!("{drawing}\n{remaining}")).unwrap_or_else(|error| {
+                exit_with_error(&format!("Unable to write {path}: {error}"))
+            });
+            println!(
+                "Wrote the drawing after {n_applied} moves and the {} remaining moves to {path}.",
+                moves.len() - n_applied
+            );
+        },
+        None => print!("{drawing}"),
+    }
+}
 
-fn add_crates(stacks: &mut Vec<Vec<String>>, line: &str) -> () {
-    let n_stacks: usize = stacks.len();
-    let crate_regex: Regex = Regex::new(CRATE_REGEX).unwrap();
 
-    for i in 0..n_stacks {
-        let start: usize = i * (CRATE_TEXT_LEN + 1);
-        let end: usize = start + CRATE_TEXT_LEN;
+// The drawing is the first record of the file, so a whole input works as a target too
+fn read_target(file_path: &str) -> Vec<Vec<String>> {
+    let contents: String = fs::read_to_string(file_path)
+        .unwrap_or_else(|error| exit_with_error(&format!("Unable to read {file_path}: {error}")));
 
-        match crate_regex.captures(&line[start..end]) {
-            Some(capture) => stacks[i].push(String::from(&capture[1])),
-            None => continue,
-        };
-    }
+    split_records(&contents).first()
+        .map(drawing::parse_stacks)
+        .transpose()
+        .unwrap_or_else(|error| exit_with_error(&format!("{file_path}, {error}")))
+        .unwrap_or_else(|| exit_with_error(&format!("{file_path} has no drawing")))
 }
 
 
-#[allow(dead_code)]
-fn move_crates_individually(stacks: &mut Vec<Vec<String>>, line: &str) -> () {
-    let (n, from, to): (usize, usize, usize) = match parse_move_line(line) {
-        Some(value) => value,
-        None => return,
+// One crate per stack, e.g. `CMZ`, or separated by spaces when the names are longer;
+// `-` is an empty stack
+fn parse_tops(tops: &str) -> Goal {
+    let names: Vec<String> = match tops.contains(char::is_whitespace) {
+        true => tops.split_whitespace().map(String::from).collect(),
+        false => tops.chars().map(String::from).collect(),
     };
 
-    for _ in 0..n {
-        let crate_name: String = stacks[from - 1].pop().unwrap();
-        stacks[to - 1].push(crate_name);
-    }
+    Goal::Tops(names.into_iter().map(|x| Some(x).filter(|x| x != "-")).collect())
 }
 
 
-fn move_crates_collectively(stacks: &mut Vec<Vec<String>>, line: &str) -> () {
-    let (n, from, to): (usize, usize, usize) = match parse_move_line(line) {
-        Some(value) => value,
-        None => return,
-    };
+// Plans from the initial drawing, ignoring the moves of the input; with an output file, the
+// drawing and the plan are written as a new input
+fn plan_moves(
+    stacks: &[Vec<String>], goal: &Goal, crane: &dyn Crane, max_moves: usize,
+    output: Option<&str>
+) {
+    goal.check(stacks).unwrap_or_else(|error| exit_with_error(&error));
+    // Each extra move multiplies the search, so the progress goes to stderr as it grows
+    let on_limit = |limit: usize| eprintln!("Looking for plans of up to {limit} moves...");
+    let plan: Vec<Move> = planner::find_plan(crane, stacks, goal, max_moves, on_limit)
+        .unwrap_or_else(|| exit_with_error(&format!(
+            "The {} can't reach the goal in {max_moves} moves or less", crane.name()
+        )));
+
+    let plan_text: String = plan.iter().map(|action| format!("{action}\n")).collect();
+    match output {
+        Some(path) => {
+            let drawing: String = drawing::render_checked(stacks)
+                .unwrap_or_else(|error| exit_with_error(&error));
+            fs::write(path, format!("{drawing}\n{plan_text}")).unwrap_or_else(|error| {
+                exit_with_error(&format!("Unable to write {path}: {error}"))
+            });
+            println!("Wrote the drawing and a plan of {} moves to {path}.", plan.len());
+        },
+        None => print!("{plan_text}"),
+    }
+}
 
-    let from_index: usize = stacks[from - 1].len() - n;
-    let mut from_clone: Vec<String> = vec![String::new(); n];
 
-    from_clone.clone_from_slice(&stacks[from - 1][from_index..]);
-    stacks[to - 1].append(&mut from_clone);
-    stacks[from - 1].truncate(from_index);
+// Stops at the first move the crane can't make, returning the stacks right before it
+fn apply_moves<'a>(
+    crane: &'a dyn Crane, stacks: &[Vec<String>], moves: &[Move]
+) -> (MoveLog<'a>, Result<(), LineError>) {
+    let mut log: MoveLog = MoveLog::new(crane, stacks);
+    let result: Result<(), LineError> = moves.iter().try_for_each(|action| log.apply(action));
+
+    (log, result)
 }
 
 
-fn get_top_crates(stacks: &Vec<Vec<String>>) -> String {
+fn get_top_crates(stacks: &[Vec<String>]) -> String {
     let mut top_crates: String = String::new();
 
     for stack in stacks {
@@ -122,15 +253,78 @@ fn get_top_crates(stacks: &Vec<Vec<String>>) -> String {
 }
 
 
-fn parse_move_line(line: &str) -> Option<(usize, usize, usize)> {
-    let move_regex: Regex = Regex::new(MOVE_ACTION_REGEX).unwrap();
+fn run_repl(initial_stacks: &[Vec<String>], moves: &[Move], crane: &dyn Crane) {
+    let mut log: MoveLog = MoveLog::new(crane, initial_stacks);
+
+    println!(
+        "Loaded {} stacks and {} moves for the {}. Type 'help' for the available commands.",
+        initial_stacks.len(), moves.len(), crane.name()
+    );
+
+    let commands: [(&str, &str); 7] = [
+        ("stack <i>", "crates of the <i>-th stack, bottom to top"),
+        ("draw", "the stacks, drawn like in the input"),
+        ("top", "sequence of top crates"),
+        ("step [n]", "apply the next [n] moves (default 1)"),
+        ("undo [n]", "undo the last [n] moves (default 1)"),
+        ("redo [n]", "redo the last [n] undone moves (default 1)"),
+        ("reset", "go back to the initial drawing"),
+    ];
+    aoc_common::run_repl("day05", &commands, |words| {
+        let n: usize = words.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        match words {
+            ["stack", i] => {
+                let index: Option<usize> = i.parse::<usize>().ok().and_then(|i| i.checked_sub(1));
+                match index.and_then(|index| log.stacks().get(index)) {
+                    Some(stack) => println!("Stack {i} (bottom to top): {}", stack.join(" ")),
+                    None => println!("There is no stack {i}."),
+                }
+            },
+            ["draw"] => match drawing::render_checked(log.stacks()) {
+                Ok(drawing) => print!("{drawing}"),
+                Err(error) => println!("{error}"),
+            },
+            ["top"] => println!(
+                "Top crates after {} moves: {}", log.n_applied(), get_top_crates(log.stacks())
+            ),
+            ["step"] | ["step", _] => {
+                for action in moves.iter().skip(log.n_applied()).take(n) {
+                    match log.apply(action) {
+                        Ok(()) => println!("{action}"),
+                        Err(error) => {
+                            println!("{error}");
+                            break;
+                        },
+                    }
+                }
+            },
+            ["undo"] | ["undo", _] => {
+                for _ in 0..n {
+                    match log.undo() {
+                        Some(action) => println!("Undid {action}"),
+                        None => {
+                            println!("There are no moves to undo.");
+                            break;
+                        },
+                    }
+                }
+            },
+            ["redo"] | ["redo", _] => {
+                for _ in 0..n {
+                    match log.redo() {
+                        Some(action) => println!("Redid {action}"),
+                        None => {
+                            println!("There are no moves to redo.");
+                            break;
+                        },
+                    }
+                }
+            },
+            ["reset"] => log = MoveLog::new(crane, initial_stacks),
+            _ => return false,
+        }
 
-    match move_regex.captures(line) {
-        Some(capture) => Some((
-            capture[1].parse::<usize>().unwrap(),
-            capture[2].parse::<usize>().unwrap(),
-            capture[3].parse::<usize>().unwrap(),
-        )),
-        None => None,
-    }
+        true
+    });
 }
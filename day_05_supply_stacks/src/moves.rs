@@ -0,0 +1,129 @@
+use std::fmt;
+
+use aoc_common::{LineError, Record};
+use regex::Regex;
+
+use crate::crane::Crane;
+
+const MOVE_ACTION_REGEX: &str = r"^move (\d+) from (\d+) to (\d+)$";
+
+
+// A move as written in the input, so its stacks start at one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub line: usize,  // 1-based, in the input
+    pub n: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Move {
+    // Whether the crane can make the move without running out of crates or stacks
+    pub fn check(&self, stacks: &[Vec<String>]) -> Result<(), LineError> {
+        let error = |reason: String| LineError {
+            line: self.line,
+            content: self.to_string(),
+            reason,
+        };
+
+        for stack in [self.from, self.to] {
+            if stack == 0 || stack > stacks.len() {
+                return Err(error(format!(
+                    "there is no stack {stack}, they go from 1 to {}", stacks.len()
+                )));
+            }
+        }
+        if self.from == self.to {
+            return Err(error(String::from("the crates can't be moved onto their own stack")));
+        }
+
+        let available: usize = stacks[self.from - 1].len();
+        match self.n <= available {
+            true => Ok(()),
+            false => Err(error(format!("stack {} only has {available} crates", self.from))),
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.n, self.from, self.to)
+    }
+}
+
+
+pub fn parse_moves(moves: &Record) -> Result<Vec<Move>, LineError> {
+    let move_regex: Regex = Regex::new(MOVE_ACTION_REGEX).unwrap();
+
+    moves.lines.iter().zip(moves.line_numbers()).map(|(content, line)| {
+        let numbers: Option<Vec<usize>> = move_regex.captures(content.trim()).and_then(|capture| {
+            (1..=3).map(|i| capture[i].parse::<usize>().ok()).collect()
+        });
+
+        match numbers.as_deref() {
+            Some(&[n, from, to]) => Ok(Move { line, n, from, to }),
+            _ => Err(LineError {
+                line,
+                content: String::from(*content),
+                reason: String::from("expected a move like 'move <n> from <stack> to <stack>'"),
+            }),
+        }
+    }).collect()
+}
+
+
+// Stacks after some moves, which can be undone and redone in order
+pub struct MoveLog<'a> {
+    crane: &'a dyn Crane,
+    stacks: Vec<Vec<String>>,
+    applied: Vec<(Move, Vec<String>)>,  // with the crates each move took, bottom to top
+    undone: Vec<Move>,
+}
+
+impl<'a> MoveLog<'a> {
+    pub fn new(crane: &'a dyn Crane, stacks: &[Vec<String>]) -> MoveLog<'a> {
+        MoveLog { crane, stacks: stacks.to_vec(), applied: Vec::new(), undone: Vec::new() }
+    }
+
+    pub fn stacks(&self) -> &[Vec<String>] {
+        &self.stacks
+    }
+
+    pub fn n_applied(&self) -> usize {
+        self.applied.len()
+    }
+
+    // A new move makes the undone ones impossible to redo
+    pub fn apply(&mut self, action: &Move) -> Result<(), LineError> {
+        action.check(&self.stacks)?;
+        self.undone.clear();
+        self.apply_checked(action.clone());
+        Ok(())
+    }
+
+    // The crates a move took are on top of its destination stack, whatever their order is
+    // there, so putting them back is the same for every crane
+    pub fn undo(&mut self) -> Option<&Move> {
+        let (action, taken): (Move, Vec<String>) = self.applied.pop()?;
+        let to: &mut Vec<String> = &mut self.stacks[action.to - 1];
+        to.truncate(to.len() - action.n);
+        self.stacks[action.from - 1].extend(taken);
+
+        self.undone.push(action);
+        self.undone.last()
+    }
+
+    pub fn redo(&mut self) -> Option<&Move> {
+        let action: Move = self.undone.pop()?;
+        self.apply_checked(action);
+        self.applied.last().map(|(action, _)| action)
+    }
+
+    fn apply_checked(&mut self, action: Move) {
+        let from: &[String] = &self.stacks[action.from - 1];
+        let taken: Vec<String> = from[from.len() - action.n..].to_vec();
+
+        self.crane.move_crates(&mut self.stacks, action.n, action.from - 1, action.to - 1);
+        self.applied.push((action, taken));
+    }
+}
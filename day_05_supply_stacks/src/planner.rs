@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::crane::Crane;
+use crate::moves::Move;
+
+
+// What the stacks should look like at the end: either every crate in place, or only the top
+// ones, where `None` is an empty stack
+pub enum Goal {
+    Stacks(Vec<Vec<String>>),
+    Tops(Vec<Option<String>>),
+}
+
+impl Goal {
+    // Rules out goals that no number of moves could reach
+    pub fn check(&self, stacks: &[Vec<String>]) -> Result<(), String> {
+        let n_stacks: usize = match self {
+            Goal::Stacks(target) => target.len(),
+            Goal::Tops(tops) => tops.len(),
+        };
+        if n_stacks != stacks.len() {
+            return Err(format!("The goal has {n_stacks} stacks instead of {}", stacks.len()));
+        }
+
+        let mut available: HashMap<&str, usize> = HashMap::new();
+        stacks.iter().flatten().for_each(|x| *available.entry(x).or_default() += 1);
+        let mut needed: HashMap<&str, usize> = HashMap::new();
+        match self {
+            Goal::Stacks(target) => target.iter().flatten()
+                .for_each(|x| *needed.entry(x).or_default() += 1),
+            Goal::Tops(tops) => tops.iter().flatten()
+                .for_each(|x| *needed.entry(x).or_default() += 1),
+        }
+
+        let exact: bool = matches!(self, Goal::Stacks(_));
+        match needed.iter().find(|(x, n)| available.get(*x).unwrap_or(&0) < n) {
+            Some((x, _)) => Err(format!("There aren't enough [{x}] crates for the goal")),
+            None if exact && needed != available => {
+                Err(String::from("The goal doesn't have the same crates as the drawing"))
+            },
+            None => Ok(()),
+        }
+    }
+
+    fn is_reached(&self, stacks: &[Vec<String>]) -> bool {
+        match self {
+            Goal::Stacks(target) => stacks == target,
+            Goal::Tops(tops) => stacks.iter().zip(tops).all(|(x, top)| x.last() == top.as_ref()),
+        }
+    }
+
+    // Never more than the moves still needed, which keeps the plans found the shortest ones
+    fn lower_bound(&self, stacks: &[Vec<String>]) -> usize {
+        match self {
+            // Crates above the first misplaced one all have to leave their stack, and every
+            // stack still missing crates has to get some, but a move only takes from one stack
+            // and puts onto one stack
+            Goal::Stacks(target) => {
+                let (mut to_empty, mut to_fill): (usize, usize) = (0, 0);
+                for (stack, target) in stacks.iter().zip(target) {
+                    let in_place: usize = stack.iter().zip(target)
+                        .take_while(|(x, y)| x == y)
+                        .count();
+                    to_empty += usize::from(stack.len() > in_place);
+                    to_fill += usize::from(target.len() > in_place);
+                }
+                to_empty.max(to_fill)
+            },
+            // Every stack with a wrong top takes part in some move. Stacks to be emptied, and
+            // the only stack holding a crate wanted on a wrong top, are the source of at least
+            // one; stacks that don't hold the crate wanted on them are the destination of at
+            // least one. A move has one source and one destination.
+            Goal::Tops(tops) => {
+                let mut is_source: Vec<bool> = vec![false; stacks.len()];
+                let mut is_destination: Vec<bool> = vec![false; stacks.len()];
+                let mut is_wrong: Vec<bool> = vec![false; stacks.len()];
+
+                for (i, (stack, top)) in stacks.iter().zip(tops).enumerate() {
+                    if stack.last() == top.as_ref() {
+                        continue;
+                    }
+                    is_wrong[i] = true;
+
+                    let Some(wanted) = top else {
+                        is_source[i] = true;
+                        continue;
+                    };
+                    is_destination[i] = !stack.contains(wanted);
+                    let mut holders = (0..stacks.len()).filter(|x| stacks[*x].contains(wanted));
+                    if let (Some(holder), None) = (holders.next(), holders.next()) {
+                        is_source[holder] = true;
+                    }
+                }
+
+                let n_sources: usize = is_source.iter().filter(|x| **x).count();
+                let n_destinations: usize = is_destination.iter().filter(|x| **x).count();
+                let n_others: usize = (0..stacks.len())
+                    .filter(|x| is_wrong[*x] && !is_source[*x] && !is_destination[*x])
+                    .count();
+                let n_ends: usize = n_sources + n_destinations + n_others;
+                n_sources.max(n_destinations).max(n_ends.div_ceil(2))
+            },
+        }
+    }
+}
+
+
+// Shortest sequence of moves that reaches the goal with the crane, if there's one with at most
+// `max_moves` of them. Uses IDA*, i.e. depth-first searches with a growing limit on the moves
+// made plus the lower bound of the moves left; `on_limit` is called as each search starts.
+pub fn find_plan(
+    crane: &dyn Crane, stacks: &[Vec<String>], goal: &Goal, max_moves: usize,
+    mut on_limit: impl FnMut(usize)
+) -> Option<Vec<Move>> {
+    let mut search: Search = Search {
+        crane,
+        goal,
+        path: Vec::new(),
+        fewest_moves: HashMap::new(),
+        next_limit: None,
+    };
+    let mut limit: usize = goal.lower_bound(stacks);
+
+    while limit <= max_moves {
+        on_limit(limit);
+        if search.search(stacks, limit) {
+            return Some(search.path);
+        }
+
+        // Without anything over the limit, every reachable arrangement was already seen
+        limit = search.next_limit.take()?;
+        search.fewest_moves.clear();
+    }
+
+    None
+}
+
+
+struct Search<'a> {
+    crane: &'a dyn Crane,
+    goal: &'a Goal,
+    path: Vec<Move>,
+    // Arrangements seen with the current limit, with the fewest moves they were reached in
+    fewest_moves: HashMap<Vec<Vec<String>>, usize>,
+    next_limit: Option<usize>,
+}
+
+impl<'a> Search<'a> {
+    fn search(&mut self, stacks: &[Vec<String>], limit: usize) -> bool {
+        let n_moves: usize = self.path.len();
+        let estimate: usize = n_moves + self.goal.lower_bound(stacks);
+        if estimate > limit {
+            self.next_limit = Some(self.next_limit.map_or(estimate, |x| x.min(estimate)));
+            return false;
+        }
+        if self.goal.is_reached(stacks) {
+            return true;
+        }
+        if self.fewest_moves.get(stacks).is_some_and(|x| *x <= n_moves) {
+            return false;
+        }
+        self.fewest_moves.insert(stacks.to_vec(), n_moves);
+
+        // Moving the crates of the last move straight back only gets to where it started
+        let last: Option<(usize, usize, usize)> = self.path.last().map(|x| (x.n, x.from, x.to));
+        let crane: &dyn Crane = self.crane;
+        let undoes_last = |n: usize, from: usize, to: usize| {
+            last == Some((n, to + 1, from + 1)) && crane.is_reversible(n)
+        };
+
+        for from in 0..stacks.len() {
+            for to in (0..stacks.len()).filter(|to| *to != from) {
+                for n in (1..=stacks[from].len()).filter(|n| !undoes_last(*n, from, to)) {
+                    let mut next: Vec<Vec<String>> = stacks.to_vec();
+                    self.crane.move_crates(&mut next, n, from, to);
+
+                    // Numbered from one within the plan
+                    let line: usize = n_moves + 1;
+                    self.path.push(Move { line, n, from: from + 1, to: to + 1 });
+                    if self.search(&next, limit) {
+                        return true;
+                    }
+                    self.path.pop();
+                }
+            }
+        }
+
+        false
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001, LimitedCrane};
+
+    // Fewest moves to the goal, going through every arrangement one move further at a time
+    fn breadth_first(
+        crane: &dyn Crane, stacks: &[Vec<String>], goal: &Goal, max_moves: usize
+    ) -> Option<usize> {
+        let mut seen: HashSet<Vec<Vec<String>>> = HashSet::from([stacks.to_vec()]);
+        let mut layer: Vec<Vec<Vec<String>>> = vec![stacks.to_vec()];
+
+        for n_moves in 0..=max_moves {
+            if layer.iter().any(|x| goal.is_reached(x)) {
+                return Some(n_moves);
+            }
+
+            let mut next_layer: Vec<Vec<Vec<String>>> = Vec::new();
+            for stacks in layer {
+                for from in 0..stacks.len() {
+                    for to in (0..stacks.len()).filter(|to| *to != from) {
+                        for n in 1..=stacks[from].len() {
+                            let mut next: Vec<Vec<String>> = stacks.clone();
+                            crane.move_crates(&mut next, n, from, to);
+                            if seen.insert(next.clone()) {
+                                next_layer.push(next);
+                            }
+                        }
+                    }
+                }
+            }
+            layer = next_layer;
+        }
+
+        None
+    }
+
+    #[test]
+    fn plans_are_as_short_as_breadth_first_search() {
+        // Small linear congruential generator, so the drawings are the same on every run
+        let mut seed: u64 = 2022;
+        let mut next = |bound: usize| -> usize {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+        let cranes: [&dyn Crane; 3] =
+            [&CrateMover9000, &CrateMover9001, &LimitedCrane { max_per_lift: 2 }];
+        let max_moves: usize = 5;
+
+        for _ in 0..200 {
+            // A few crates with repeated names, spread over three stacks twice
+            let crates: Vec<String> = (0..2 + next(3)).map(|_| ["A", "B", "C"][next(3)])
+                .map(String::from)
+                .collect();
+            let mut spread = || -> Vec<Vec<String>> {
+                let mut stacks: Vec<Vec<String>> = vec![Vec::new(); 3];
+                let mut shuffled: Vec<String> = crates.clone();
+                while !shuffled.is_empty() {
+                    let crate_name: String = shuffled.swap_remove(next(shuffled.len()));
+                    stacks[next(3)].push(crate_name);
+                }
+                stacks
+            };
+            let (stacks, target): (Vec<Vec<String>>, Vec<Vec<String>>) = (spread(), spread());
+
+            let goals: [Goal; 2] = [
+                Goal::Tops(target.iter().map(|x| x.last().cloned()).collect()),
+                Goal::Stacks(target),
+            ];
+            for (goal, crane) in goals.iter().flat_map(|x| cranes.iter().map(move |y| (x, y))) {
+                let expected: Option<usize> = breadth_first(*crane, &stacks, goal, max_moves);
+                let plan: Option<Vec<Move>> = find_plan(*crane, &stacks, goal, max_moves, |_| {});
+                assert_eq!(plan.as_ref().map(|x| x.len()), expected, "{stacks:?} {}", crane.name());
+
+                let mut arrangement: Vec<Vec<String>> = stacks.clone();
+                for action in plan.iter().flatten() {
+                    assert!(arrangement[action.from - 1].len() >= action.n);
+                    crane.move_crates(&mut arrangement, action.n, action.from - 1, action.to - 1);
+                }
+                assert!(plan.is_none() || goal.is_reached(&arrangement));
+            }
+        }
+    }
+}
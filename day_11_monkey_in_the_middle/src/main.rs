@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
+use std::env;
 use std::fs;
 
+use aoc_common::split_records;
 use regex::{Regex, Captures};
 
 const INPUT_FILE_PATH: &str = "input";
@@ -13,8 +15,15 @@ fn main() {
 
     let mut monkeys: Vec<Monkey> = generate_monkey_vector(&input);
     let divisible_by_all: u128 = monkeys.iter().map(|x| x.divisible_by).product();
-    execute_rounds(&mut monkeys, divisible_by_all);
-    let monkey_business: u128 = compute_monkey_business(&mut monkeys);
+
+    if env::args().nth(1).as_deref() == Some("repl") {
+        run_repl(&monkeys, divisible_by_all);
+        return;
+    }
+
+    execute_rounds(&mut monkeys, divisible_by_all, NUMBER_OF_ROUNDS);
+    let monkey_business: u128 = compute_monkey_business(&mut monkeys)
+        .expect("Monkey business needs at least two monkeys...");
 
     println!("Number of monkey business: {monkey_business}");
 }
@@ -28,7 +37,7 @@ fn read_input_file(file_path: &str) -> String {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Monkey {
     items: VecDeque<u128>,
     operation: (char, String),
@@ -78,7 +87,7 @@ impl Monkey {
 
             // Line below for Part 1
             // worry_level = (worry_level as f64 / 3.0).floor() as u128;
-            worry_level = worry_level % decrease_factor;
+            worry_level %= decrease_factor;
 
             match self.test_new_worry_level(worry_level) {
                 true => items_to_true.push_back(worry_level),
@@ -102,24 +111,20 @@ impl Monkey {
     }
 
     pub fn test_new_worry_level(&self, worry_level: u128) -> bool {
-        if worry_level % self.divisible_by == 0 {
-            return true;
-        }
-
-        false
+        worry_level.is_multiple_of(self.divisible_by)
     }
 }
 
 
 fn generate_monkey_vector(input: &str) -> Vec<Monkey> {
     Vec::from_iter(
-        input.split("\n\n").map(|spec| Monkey::new(spec))
+        split_records(input).iter().map(|record| Monkey::new(&record.text()))
     )
 }
 
 
-fn execute_rounds(monkeys: &mut Vec<Monkey>, decrease_factor: u128) -> () {
-    for _ in 0..NUMBER_OF_ROUNDS {
+fn execute_rounds(monkeys: &mut [Monkey], decrease_factor: u128, n_rounds: usize) {
+    for _ in 0..n_rounds {
         for i in 0..monkeys.len() {
             let (mut to_true, mut to_false) = monkeys[i].take_turn(decrease_factor);
             let monkey_if_true: usize = monkeys[i].monkey_if_true;
@@ -131,7 +136,52 @@ fn execute_rounds(monkeys: &mut Vec<Monkey>, decrease_factor: u128) -> () {
 }
 
 
-fn compute_monkey_business(monkeys: &mut Vec<Monkey>) -> u128 {
+// Needs at least two monkeys
+fn compute_monkey_business(monkeys: &mut [Monkey]) -> Option<u128> {
     monkeys.sort_by_key(|m| m.items_inspected);
-    monkeys[monkeys.len() - 1].items_inspected * monkeys[monkeys.len() - 2].items_inspected
+    match monkeys {
+        [.., second, first] => Some(first.items_inspected * second.items_inspected),
+        _ => None,
+    }
+}
+
+
+fn run_repl(initial_monkeys: &[Monkey], decrease_factor: u128) {
+    println!(
+        "Loaded {} monkeys. Type 'help' for the available commands.", initial_monkeys.len()
+    );
+
+    let commands: [(&str, &str); 2] = [
+        ("round <n>", "state of every monkey after <n> rounds"),
+        ("monkey <i>", "initial specification of the <i>-th monkey"),
+    ];
+    aoc_common::run_repl("day11", &commands, |words| {
+        match words {
+            ["round", n] => match n.parse::<usize>() {
+                Ok(n) => {
+                    let mut monkeys: Vec<Monkey> = initial_monkeys.to_vec();
+                    execute_rounds(&mut monkeys, decrease_factor, n);
+
+                    for (i, monkey) in monkeys.iter().enumerate() {
+                        println!(
+                            "Monkey {i}: inspected {} items, holding {:?}",
+                            monkey.items_inspected, monkey.items
+                        );
+                    }
+                    match compute_monkey_business(&mut monkeys) {
+                        Some(business) => println!("Monkey business after {n} rounds: {business}"),
+                        None => println!("Monkey business needs at least two monkeys."),
+                    }
+                },
+                Err(_) => println!("'{n}' is not a valid number of rounds."),
+            },
+            ["monkey", i] => match i.parse::<usize>().ok().and_then(|i| initial_monkeys.get(i)) {
+                Some(monkey) => println!("{monkey:?}"),
+                None => println!("There is no monkey {i}."),
+            },
+            _ => return false,
+        }
+
+        true
+    });
 }
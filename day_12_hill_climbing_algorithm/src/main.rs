@@ -1,7 +1,8 @@
+use std::env;
 use std::fs;
 
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::algo::dijkstra;
+use petgraph::algo::{astar, dijkstra};
 
 const INPUT_FILE_PATH: &str = "input";
 
@@ -19,6 +20,11 @@ fn main() {
     let (start, end): (usize, usize) = find_start_and_end_nodes(&mut area_matrix);
     let graph: DiGraph<usize, ()> = build_unidirected_graph(&area_matrix);
 
+    if env::args().nth(1).as_deref() == Some("repl") {
+        run_repl(&graph, &area_matrix, start, end);
+        return;
+    }
+
     // Part 1
     let minimum_distance = compute_minimum_distance(&graph, start, end);
 
@@ -46,9 +52,9 @@ fn build_area_matrix(input: &str) -> Vec<Vec<char>> {
 }
 
 
-fn find_start_and_end_nodes(matrix: &mut Vec<Vec<char>>) -> (usize, usize) {
+fn find_start_and_end_nodes(matrix: &mut [Vec<char>]) -> (usize, usize) {
     let columns: usize = matrix[0].len();
-    let matrix_flat: Vec<char> = matrix.iter().flatten().map(|c| *c).collect();
+    let matrix_flat: Vec<char> = matrix.iter().flatten().copied().collect();
 
     // Start
     let start: usize = matrix_flat.iter().position(|x| *x == START_POINT_CHAR).unwrap();
@@ -62,7 +68,7 @@ fn find_start_and_end_nodes(matrix: &mut Vec<Vec<char>>) -> (usize, usize) {
 }
 
 
-fn build_unidirected_graph(matrix: &Vec<Vec<char>>) -> DiGraph<usize, ()> {
+fn build_unidirected_graph(matrix: &[Vec<char>]) -> DiGraph<usize, ()> {
     let rows: usize = matrix.len();
     let columns: usize = matrix[0].len();
 
@@ -77,7 +83,7 @@ fn build_unidirected_graph(matrix: &Vec<Vec<char>>) -> DiGraph<usize, ()> {
 
     for i in 0..rows {
         for j in 0..columns {
-            let neighbors: Vec<(usize, usize)> = vec![
+            let neighbors: Vec<(usize, usize)> = [
                 (i as i64 + 1, j as i64),
                 (i as i64 - 1, j as i64),
                 (i as i64, j as i64 + 1),
@@ -111,7 +117,7 @@ fn compute_minimum_distance(
 }
 
 
-fn get_possible_starting_points(matrix: &Vec<Vec<char>>) -> Vec<usize> {
+fn get_possible_starting_points(matrix: &[Vec<char>]) -> Vec<usize> {
     matrix.iter().flatten().enumerate()
         .filter(|(_, c)| **c == MIN_HEIGHT_CHAR)
         .map(|(i, _)| i).collect::<Vec<usize>>()
@@ -119,9 +125,74 @@ fn get_possible_starting_points(matrix: &Vec<Vec<char>>) -> Vec<usize> {
 
 
 fn compute_minimum_distance_multiple(
-    graph: &DiGraph<usize, ()>, starts: &Vec<usize>, end: usize
+    graph: &DiGraph<usize, ()>, starts: &[usize], end: usize
 ) -> i32 {
     starts.iter()
-        .map(|start| compute_minimum_distance(&graph, *start, end))
+        .map(|start| compute_minimum_distance(graph, *start, end))
         .min().unwrap()
 }
+
+
+fn compute_shortest_path(
+    graph: &DiGraph<usize, ()>, start: usize, end: usize
+) -> Option<(i32, Vec<usize>)> {
+    astar(
+        graph, NodeIndex::new(start), |node| node == NodeIndex::new(end), |_| 1, |_| 0
+    ).map(|(distance, path)| (distance, path.iter().map(|node| node.index()).collect()))
+}
+
+
+fn run_repl(graph: &DiGraph<usize, ()>, matrix: &[Vec<char>], start: usize, end: usize) {
+    let columns: usize = matrix[0].len();
+    let parse_point = |word: &str| -> Option<usize> {
+        match word {
+            "S" => Some(start),
+            "E" => Some(end),
+            _ => {
+                let (row, column) = word.split_once(',')?;
+                let (row, column): (usize, usize) = (row.parse().ok()?, column.parse().ok()?);
+                (row < matrix.len() && column < columns).then_some(row * columns + column)
+            },
+        }
+    };
+
+    println!(
+        "Loaded a {}x{columns} area. Type 'help' for the available commands.", matrix.len()
+    );
+
+    let lowest_help: String = format!("minimum distance from any '{MIN_HEIGHT_CHAR}' to 'E'");
+    let commands: [(&str, &str); 4] = [
+        ("path <a> <b>", "shortest path between two points, written as 'S', 'E'"),
+        ("", "or '<row>,<column>'"),
+        ("height <a>", "height of a point"),
+        ("lowest", &lowest_help),
+    ];
+    aoc_common::run_repl("day12", &commands, |words| {
+        match words {
+            ["path", from, to] => match (parse_point(from), parse_point(to)) {
+                (Some(a), Some(b)) => match compute_shortest_path(graph, a, b) {
+                    Some((distance, path)) => {
+                        let steps: Vec<String> = path.iter()
+                            .map(|node| format!("({},{})", node / columns, node % columns))
+                            .collect();
+                        println!("Length {distance}: {}", steps.join(" -> "));
+                    },
+                    None => println!("There is no path from {from} to {to}."),
+                },
+                _ => println!("Points must be 'S', 'E' or '<row>,<column>' inside the area."),
+            },
+            ["height", point] => match parse_point(point) {
+                Some(node) => println!("{}", matrix[node / columns][node % columns]),
+                None => println!("Points must be 'S', 'E' or '<row>,<column>' inside the area."),
+            },
+            ["lowest"] => {
+                let starts: Vec<usize> = get_possible_starting_points(matrix);
+                let minimum_lowest: i32 = compute_minimum_distance_multiple(graph, &starts, end);
+                println!("The minimum distance from any '{MIN_HEIGHT_CHAR}' is: {minimum_lowest}");
+            },
+            _ => return false,
+        }
+
+        true
+    });
+}